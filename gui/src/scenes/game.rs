@@ -5,9 +5,57 @@ use macroquad::{
     prelude::*,
 };
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
 
 use state::prelude::*;
 
+/// A search for the engine's move, running on its own thread so that the window keeps
+/// drawing frames while the engine thinks. Dropping it stops the search.
+struct EngineSearch {
+    /// Hash of the position being searched, to tell whether the answer still applies.
+    hash: u64,
+    stop: Arc<AtomicBool>,
+    best_move: Receiver<Option<Move>>,
+}
+
+impl EngineSearch {
+    fn start(game_state: &GameState, limits: &SearchLimits) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let limits = SearchLimits {
+            stop: Some(Arc::clone(&stop)),
+            ..limits.clone()
+        };
+        let hash = game_state.hash();
+        let game_state = game_state.clone();
+        let (sender, best_move) = mpsc::channel();
+        thread::spawn(move || {
+            // Nobody is waiting for the answer if the search was abandoned
+            let _ = sender.send(game_state.search(&limits).best_move);
+        });
+        Self {
+            hash,
+            stop,
+            best_move,
+        }
+    }
+}
+
+impl Drop for EngineSearch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Colors laid over the squares of the board.
+pub struct HighlightColors {
+    pub check: Color,
+    pub last_move: Color,
+    pub selected: Color,
+}
+
 pub struct Game {
     piece_textures: HashMap<Square, Texture2D>,
     piece_texture_params: DrawTextureParams,
@@ -25,11 +73,15 @@ pub struct Game {
 
     first_square_selected: Option<BoardCoordinates>,
     second_square_selected: Option<BoardCoordinates>,
+    /// Why the last move attempt was refused, shown until the next move.
+    illegal_move_reason: Option<String>,
 
     move_sound: Sound,
     capture_sound: Sound,
 
     engine_turn: Option<Player>,
+    search_limits: SearchLimits,
+    engine_search: Option<EngineSearch>,
 }
 
 impl Game {
@@ -39,16 +91,16 @@ impl Game {
             Texture2D::from_file_with_format(assets::board::BOARD, Some(ImageFormat::Png));
         board_texture.set_filter(FilterMode::Nearest);
 
-        let check_color = assets::colors::CHECK;
-        let move_color = assets::colors::LAST_MOVE;
-        let selected_color = assets::colors::SQUARE_SELECTED;
+        let highlight_colors = HighlightColors {
+            check: assets::colors::CHECK,
+            last_move: assets::colors::LAST_MOVE,
+            selected: assets::colors::SQUARE_SELECTED,
+        };
         let engine_turn = None;
         Self::new(
             piece_textures,
             board_texture,
-            check_color,
-            move_color,
-            selected_color,
+            highlight_colors,
             move_sound,
             capture_sound,
             engine_turn,
@@ -60,9 +112,7 @@ impl Game {
     pub fn new(
         piece_textures: HashMap<Square, Texture2D>,
         board_texture: Texture2D,
-        check_color: Color,
-        move_color: Color,
-        selected_color: Color,
+        highlight_colors: HighlightColors,
         move_sound: Sound,
         capture_sound: Sound,
         engine_turn: Option<Player>,
@@ -76,6 +126,14 @@ impl Game {
 
         let first_square_selected: Option<BoardCoordinates> = None;
         let second_square_selected: Option<BoardCoordinates> = None;
+        let illegal_move_reason = None;
+
+        // Iterative deepening answers with the deepest iteration finished within the time limit
+        let search_limits = SearchLimits {
+            depth: MAX_DEPTH,
+            time: Some(std::time::Duration::from_secs(2)),
+            ..SearchLimits::default()
+        };
         Self {
             piece_textures,
             piece_texture_params: piece_textures_params,
@@ -87,21 +145,25 @@ impl Game {
             x_padding,
             y_padding,
 
-            check_color,
-            move_color,
-            selected_color,
+            check_color: highlight_colors.check,
+            move_color: highlight_colors.last_move,
+            selected_color: highlight_colors.selected,
 
             first_square_selected,
             second_square_selected,
+            illegal_move_reason,
 
             move_sound,
             capture_sound,
             engine_turn,
+            search_limits,
+            engine_search: None,
         }
     }
 
     pub fn set_engine_turn(&mut self, turn: Option<Player>) {
         self.engine_turn = turn;
+        self.engine_search = None;
     }
 
     pub fn get_square_size(&self) -> f32 {
@@ -140,19 +202,15 @@ impl Game {
     }
 
     async fn update_logic(&mut self, game_state: &mut GameState) {
-        if Some(game_state.get_turn()) == self.engine_turn {
-            if let Some(to_move) = game_state.best_move() {
-                game_state.make_new_move(to_move);
-                if to_move.piece_captured == Square::Empty {
-                    play_sound_once(&self.move_sound);
-                } else {
-                    play_sound_once(&self.capture_sound);
-                }
-            }
+        if Some(game_state.get_turn()) == self.engine_turn && !game_state.is_game_over() {
+            self.poll_engine(game_state);
             return;
         }
+        self.engine_search = None;
+
         // Undo last move
         if is_key_pressed(KeyCode::Z) {
+            self.illegal_move_reason = None;
             game_state.undo_last_move();
             if self.engine_turn.is_some() {
                 game_state.undo_last_move();
@@ -195,58 +253,28 @@ impl Game {
                     self.second_square_selected = Some(square_clicked);
                 }
 
-                if self.first_square_selected.is_some() && self.second_square_selected.is_some() {
-                    let mut potential_move = Move::new(
-                        self.first_square_selected.unwrap(),
-                        self.second_square_selected.unwrap(),
-                        None,
-                        game_state,
-                    );
-
-                    if game_state.get_valid_moves().contains(&potential_move) {
-                        if potential_move.piece_moved
-                            == Square::Occupied(Player::White, Piece::Pawn)
-                            && potential_move.end.row() == 0
-                        {
-                            let promotion_piece =
-                                self.get_promotion_piece(Player::White, game_state).await;
-                            potential_move = Move::new(
-                                potential_move.start,
-                                potential_move.end,
-                                Some(SpecialMove::PawnPromotion(promotion_piece)),
-                                game_state,
-                            );
-                        } else if potential_move.piece_moved
-                            == Square::Occupied(Player::Black, Piece::Pawn)
-                            && potential_move.end.row() == 7
+                if let (Some(start), Some(end)) =
+                    (self.first_square_selected, self.second_square_selected)
+                {
+                    let mut result = game_state.try_make_move(start, end, None);
+                    if result == Err(IllegalMoveError::MissingPromotion) {
+                        if let Square::Occupied(_, piece) = self
+                            .get_promotion_piece(game_state.get_turn(), game_state)
+                            .await
                         {
-                            let promotion_piece =
-                                self.get_promotion_piece(Player::Black, game_state).await;
-                            potential_move = Move::new(
-                                potential_move.start,
-                                potential_move.end,
-                                Some(SpecialMove::PawnPromotion(promotion_piece)),
-                                game_state,
-                            );
-                        }
-                    }
-
-                    let mut is_move_valid = false;
-                    for real_move in game_state.get_valid_moves() {
-                        if &potential_move == real_move {
-                            // Use the generated move in game state instead of the one generated
-                            // with mouse input, to preserve special move's properties
-                            potential_move = *real_move;
-                            is_move_valid = true;
+                            result = game_state.try_make_move(start, end, Some(piece));
                         }
                     }
-                    if is_move_valid {
-                        game_state.make_new_move(potential_move);
-                        if potential_move.piece_captured == Square::Empty {
-                            play_sound_once(&self.move_sound);
-                        } else {
-                            play_sound_once(&self.capture_sound);
+                    match result {
+                        Ok(played) => {
+                            self.illegal_move_reason = None;
+                            if played.piece_captured == Square::Empty {
+                                play_sound_once(&self.move_sound);
+                            } else {
+                                play_sound_once(&self.capture_sound);
+                            }
                         }
+                        Err(error) => self.illegal_move_reason = Some(error.to_string()),
                     }
 
                     self.first_square_selected = None;
@@ -256,6 +284,34 @@ impl Game {
         }
     }
 
+    /// Starts the engine thinking about the current position, or plays its move once it has
+    /// one. Called every frame while it is the engine's turn.
+    fn poll_engine(&mut self, game_state: &mut GameState) {
+        let hash = game_state.hash();
+        let search = match &self.engine_search {
+            Some(search) if search.hash == hash => search,
+            _ => {
+                self.engine_search = Some(EngineSearch::start(game_state, &self.search_limits));
+                return;
+            }
+        };
+        let best_move = match search.best_move.try_recv() {
+            Ok(best_move) => best_move,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        self.engine_search = None;
+
+        if let Some(to_move) = best_move {
+            game_state.make_new_move(to_move);
+            if to_move.piece_captured == Square::Empty {
+                play_sound_once(&self.move_sound);
+            } else {
+                play_sound_once(&self.capture_sound);
+            }
+        }
+    }
+
     async fn get_promotion_piece(&mut self, color: Player, game_state: &GameState) -> Square {
         let pieces = [
             [
@@ -321,6 +377,37 @@ impl Game {
         self.draw_board();
         self.draw_highlights(game_state);
         self.draw_pieces(game_state);
+        self.draw_evaluation(game_state);
+        self.draw_illegal_move_reason();
+    }
+
+    fn draw_evaluation(&self, game_state: &GameState) {
+        // Shown from White's point of view, in pawns, like most chess software does
+        let score = match game_state.get_turn() {
+            Player::White => evaluate(game_state),
+            Player::Black => -evaluate(game_state),
+        };
+        let font_size = self.square_size / 4.0;
+        draw_text(
+            format!("{:+.2}", score as f32 / 100.0),
+            self.x_padding,
+            self.y_padding - font_size / 4.0,
+            font_size,
+            assets::colors::FONT,
+        );
+    }
+
+    fn draw_illegal_move_reason(&self) {
+        if let Some(reason) = &self.illegal_move_reason {
+            let font_size = self.square_size / 4.0;
+            draw_text(
+                reason,
+                self.x_padding,
+                self.get_board_end().1 + font_size,
+                font_size,
+                assets::colors::FONT,
+            );
+        }
     }
 
     fn draw_board(&self) {
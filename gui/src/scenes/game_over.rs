@@ -37,7 +37,7 @@ impl GameOver {
         );
 
         draw_text(
-            game_state.get_game_result().unwrap().to_str(),
+            game_state.get_game_result().unwrap().to_string(),
             start.0,
             (end.1 + start.1) / 2.0 - font_size / 2.0,
             font_size,
@@ -0,0 +1,260 @@
+use std::sync::OnceLock;
+
+use super::magics::{BISHOP_MAGICS, ROOK_MAGICS};
+use super::Bitboard;
+use crate::prelude::*;
+
+const KNIGHT_JUMPS: [(isize, isize); 8] = [
+    (1, 2),
+    (-1, 2),
+    (1, -2),
+    (-1, -2),
+    (2, 1),
+    (-2, 1),
+    (2, -1),
+    (-2, -1),
+];
+const KING_STEPS: [(isize, isize); 8] = [
+    (0, 1),
+    (0, -1),
+    (1, 0),
+    (-1, 0),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+const ROOK_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+const KNIGHT_ATTACKS: [Bitboard; 64] = step_attacks(&KNIGHT_JUMPS);
+const KING_ATTACKS: [Bitboard; 64] = step_attacks(&KING_STEPS);
+/// White pawns capture towards row 0, black pawns towards row 7.
+const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    step_attacks(&[(-1, -1), (-1, 1)]),
+    step_attacks(&[(1, -1), (1, 1)]),
+];
+
+const fn step_attacks(steps: &[(isize, isize)]) -> [Bitboard; 64] {
+    let mut attacks = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < steps.len() {
+            let row = (square / 8) as isize + steps[i].0;
+            let col = (square % 8) as isize + steps[i].1;
+            if row >= 0 && row < 8 && col >= 0 && col < 8 {
+                attacks[square] |= 1 << (row * 8 + col);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    attacks
+}
+
+pub fn knight_attacks(square: usize) -> Bitboard {
+    KNIGHT_ATTACKS[square]
+}
+
+pub fn king_attacks(square: usize) -> Bitboard {
+    KING_ATTACKS[square]
+}
+
+/// Squares a pawn of `player` standing on `square` captures on.
+pub fn pawn_attacks(player: Player, square: usize) -> Bitboard {
+    PAWN_ATTACKS[player as usize][square]
+}
+
+pub fn rook_attacks(square: usize, occupancy: Bitboard) -> Bitboard {
+    sliding_attacks().rook[square].attacks(occupancy)
+}
+
+pub fn bishop_attacks(square: usize, occupancy: Bitboard) -> Bitboard {
+    sliding_attacks().bishop[square].attacks(occupancy)
+}
+
+pub fn queen_attacks(square: usize, occupancy: Bitboard) -> Bitboard {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+/// Squares strictly between two squares on the same rank, file or diagonal, or no squares if
+/// they are not lined up.
+pub fn between(from: usize, to: usize) -> Bitboard {
+    let row_step = (to / 8) as isize - (from / 8) as isize;
+    let col_step = (to % 8) as isize - (from % 8) as isize;
+    if from == to || (row_step != 0 && col_step != 0 && row_step.abs() != col_step.abs()) {
+        return 0;
+    }
+
+    let step = row_step.signum() * 8 + col_step.signum();
+    let mut between = 0;
+    let mut square = from as isize + step;
+    while square != to as isize {
+        between |= 1 << square;
+        square += step;
+    }
+    between
+}
+
+/// Squares reached by sliding from `square` in each direction, up to and including the first
+/// occupied square.
+fn ray_attacks(square: usize, occupancy: Bitboard, directions: &[(isize, isize)]) -> Bitboard {
+    let mut attacks = 0;
+    for (row_step, col_step) in directions {
+        let mut row = (square / 8) as isize + row_step;
+        let mut col = (square % 8) as isize + col_step;
+        while (0..8).contains(&row) && (0..8).contains(&col) {
+            let bit = 1 << (row * 8 + col);
+            attacks |= bit;
+            if occupancy & bit != 0 {
+                break;
+            }
+            row += row_step;
+            col += col_step;
+        }
+    }
+    attacks
+}
+
+/// Squares whose occupancy changes the attacks of a slider on `square`. The last square of each
+/// ray is left out, since it is attacked whether or not it is occupied.
+fn relevant_occupancy(square: usize, directions: &[(isize, isize)]) -> Bitboard {
+    let mut mask = 0;
+    for (row_step, col_step) in directions {
+        let mut row = (square / 8) as isize + row_step;
+        let mut col = (square % 8) as isize + col_step;
+        while (0..8).contains(&(row + row_step)) && (0..8).contains(&(col + col_step)) {
+            mask |= 1 << (row * 8 + col);
+            row += row_step;
+            col += col_step;
+        }
+    }
+    mask
+}
+
+/// Maps the relevant occupancy of one square to an index into the attack table by multiplying
+/// it with a magic number that packs its bits into the top of the product.
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    attacks: Vec<Bitboard>,
+}
+
+impl Magic {
+    fn index(&self, occupancy: Bitboard) -> usize {
+        ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    fn attacks(&self, occupancy: Bitboard) -> Bitboard {
+        self.attacks[self.index(occupancy)]
+    }
+
+    fn new(square: usize, directions: &[(isize, isize)], magic: u64) -> Self {
+        let mask = relevant_occupancy(square, directions);
+        let bits = mask.count_ones();
+        let mut magic = Self {
+            mask,
+            magic,
+            shift: 64 - bits,
+            attacks: vec![0; 1 << bits],
+        };
+
+        // Every subset of the mask, using the Carry-Rippler trick
+        let mut occupancy: Bitboard = 0;
+        loop {
+            let index = magic.index(occupancy);
+            magic.attacks[index] = ray_attacks(square, occupancy, directions);
+            occupancy = occupancy.wrapping_sub(mask) & mask;
+            if occupancy == 0 {
+                break;
+            }
+        }
+        magic
+    }
+}
+
+struct SlidingAttacks {
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+}
+
+/// The attack tables are filled in the first time sliding attacks are needed.
+fn sliding_attacks() -> &'static SlidingAttacks {
+    static SLIDING_ATTACKS: OnceLock<SlidingAttacks> = OnceLock::new();
+    SLIDING_ATTACKS.get_or_init(|| SlidingAttacks {
+        rook: (0..64)
+            .map(|square| Magic::new(square, &ROOK_DIRECTIONS, ROOK_MAGICS[square]))
+            .collect(),
+        bishop: (0..64)
+            .map(|square| Magic::new(square, &BISHOP_DIRECTIONS, BISHOP_MAGICS[square]))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::{bit, square_index};
+
+    fn square(name: &str) -> usize {
+        square_index(name.parse().unwrap())
+    }
+
+    fn set(names: &[&str]) -> Bitboard {
+        names
+            .iter()
+            .fold(0, |set, name| set | bit(name.parse().unwrap()))
+    }
+
+    #[test]
+    fn step_attacks() {
+        assert_eq!(knight_attacks(square("a8")), set(&["b6", "c7"]));
+        assert_eq!(king_attacks(square("h1")), set(&["g1", "g2", "h2"]));
+        assert_eq!(
+            pawn_attacks(Player::White, square("e4")),
+            set(&["d5", "f5"])
+        );
+        assert_eq!(pawn_attacks(Player::Black, square("a5")), set(&["b4"]));
+    }
+
+    #[test]
+    fn magics_match_ray_attacks() {
+        // Xorshift, for occupancies that are neither empty nor full
+        let mut state: u64 = 1;
+        let mut random = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for square in 0..64 {
+            for _ in 0..32 {
+                let occupancy = random() & random();
+                assert_eq!(
+                    rook_attacks(square, occupancy),
+                    ray_attacks(square, occupancy, &ROOK_DIRECTIONS)
+                );
+                assert_eq!(
+                    bishop_attacks(square, occupancy),
+                    ray_attacks(square, occupancy, &BISHOP_DIRECTIONS)
+                );
+            }
+        }
+
+        let occupancy = set(&["d6", "f4", "b4"]);
+        assert_eq!(
+            rook_attacks(square("d4"), occupancy),
+            set(&["d5", "d6", "d3", "d2", "d1", "c4", "b4", "e4", "f4"])
+        );
+    }
+
+    #[test]
+    fn squares_between() {
+        assert_eq!(between(square("a1"), square("d4")), set(&["b2", "c3"]));
+        assert_eq!(between(square("e8"), square("e5")), set(&["e7", "e6"]));
+        assert_eq!(between(square("e1"), square("f1")), 0);
+        assert_eq!(between(square("a1"), square("b3")), 0);
+    }
+}
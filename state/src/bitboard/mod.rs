@@ -0,0 +1,74 @@
+//! Sets of squares packed into a `u64`, with bit `row * 8 + col` standing for the square at
+//! that row and col, so bit 0 is a8 and bit 63 is h1.
+
+mod attacks;
+mod magics;
+
+pub use attacks::{
+    between, bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks,
+    rook_attacks,
+};
+
+use crate::prelude::*;
+
+pub type Bitboard = u64;
+
+/// Every square of the same colour as a8.
+pub const LIGHT_SQUARES: Bitboard = 0xaa55_aa55_aa55_aa55;
+
+/// Every square on the a-file.
+const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+
+pub fn square_index(coordinates: BoardCoordinates) -> usize {
+    coordinates.row() * 8 + coordinates.col()
+}
+
+pub fn coordinates(square_index: usize) -> BoardCoordinates {
+    BoardCoordinates::new(square_index / 8, square_index % 8)
+}
+
+pub fn bit(coordinates: BoardCoordinates) -> Bitboard {
+    1 << square_index(coordinates)
+}
+
+/// Every square on the file `col`.
+pub fn file(col: usize) -> Bitboard {
+    FILE_A << col
+}
+
+/// Iterates over the index of every square in the set, from a8 to h1.
+pub fn squares(mut bitboard: Bitboard) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            None
+        } else {
+            let square_index = bitboard.trailing_zeros() as usize;
+            bitboard &= bitboard - 1;
+            Some(square_index)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_indices() {
+        let e4: BoardCoordinates = "e4".parse().unwrap();
+        assert_eq!(square_index(e4), 36);
+        assert_eq!(coordinates(36), e4);
+        assert_eq!(
+            squares(bit(e4) | 1 | 1 << 63).collect::<Vec<_>>(),
+            vec![0, 36, 63]
+        );
+        assert_ne!(LIGHT_SQUARES & 1, 0);
+        assert_ne!(LIGHT_SQUARES & 1 << 63, 0);
+        assert_ne!(LIGHT_SQUARES & bit(e4), 0);
+        assert_eq!(LIGHT_SQUARES & bit("d4".parse().unwrap()), 0);
+        assert_eq!(
+            squares(file(4)).collect::<Vec<_>>(),
+            [4, 12, 20, 28, 36, 44, 52, 60]
+        );
+    }
+}
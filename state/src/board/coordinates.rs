@@ -1,4 +1,7 @@
-#[derive(Copy, Clone, Eq, PartialEq)]
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct BoardCoordinates {
     row: usize,
     col: usize,
@@ -20,6 +23,39 @@ impl BoardCoordinates {
     }
 }
 
+impl fmt::Display for BoardCoordinates {
+    /// Writes the square in algebraic notation, so row 0, col 0 is `a8`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.col as u8) as char, 8 - self.row)
+    }
+}
+
+impl FromStr for BoardCoordinates {
+    type Err = ParseCoordinatesError;
+
+    /// Reads a square in algebraic notation, so `e4` is row 4, col 4.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Ok(Self::new((b'8' - rank) as usize, (file - b'a') as usize))
+            }
+            _ => Err(ParseCoordinatesError(name.to_string())),
+        }
+    }
+}
+
+/// The text given to [`BoardCoordinates::from_str`] is not a square like `e4`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseCoordinatesError(String);
+
+impl fmt::Display for ParseCoordinatesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a square in algebraic notation", self.0)
+    }
+}
+
+impl std::error::Error for ParseCoordinatesError {}
+
 #[cfg(test)]
 mod tests {
     use super::BoardCoordinates;
@@ -42,6 +78,18 @@ mod tests {
         BoardCoordinates::new(8, 8);
     }
 
+    #[test]
+    fn algebraic_notation() {
+        let e4 = BoardCoordinates::new(4, 4);
+        assert_eq!(e4.to_string(), "e4");
+        assert_eq!("e4".parse(), Ok(e4));
+        assert_eq!("a8".parse(), Ok(BoardCoordinates::new(0, 0)));
+        assert_eq!("h1".parse(), Ok(BoardCoordinates::new(7, 7)));
+        for name in ["", "e", "i4", "e9", "e0", "E4", "e44"] {
+            assert!(name.parse::<BoardCoordinates>().is_err(), "{name}");
+        }
+    }
+
     #[test]
     fn valid_coordinates() {
         for row in 0..=7 {
@@ -1,5 +1,7 @@
 mod coordinates;
 mod initial_position;
+mod square_set;
 
-pub use coordinates::BoardCoordinates;
+pub use coordinates::{BoardCoordinates, ParseCoordinatesError};
 pub use initial_position::INITIAL_POSITION;
+pub use square_set::{SquareSet, Squares};
@@ -0,0 +1,98 @@
+use crate::bitboard::{self, Bitboard};
+use crate::prelude::*;
+
+/// A set of squares of the board, such as the squares a player attacks.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SquareSet(Bitboard);
+
+impl SquareSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, square: BoardCoordinates) -> bool {
+        self.0 & bitboard::bit(square) != 0
+    }
+
+    pub fn insert(&mut self, square: BoardCoordinates) {
+        self.0 |= bitboard::bit(square);
+    }
+
+    pub fn remove(&mut self, square: BoardCoordinates) {
+        self.0 &= !bitboard::bit(square);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub(crate) fn from_bitboard(bitboard: Bitboard) -> Self {
+        Self(bitboard)
+    }
+
+    /// Iterates over the squares in the set, from a8 to h1 row by row.
+    pub fn iter(&self) -> Squares {
+        Squares(self.0)
+    }
+}
+
+impl FromIterator<BoardCoordinates> for SquareSet {
+    fn from_iter<I: IntoIterator<Item = BoardCoordinates>>(squares: I) -> Self {
+        let mut set = Self::new();
+        for square in squares {
+            set.insert(square);
+        }
+        set
+    }
+}
+
+impl IntoIterator for SquareSet {
+    type Item = BoardCoordinates;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The squares of a [`SquareSet`], from a8 to h1 row by row.
+#[derive(Clone, Debug)]
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = BoardCoordinates;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let square_index = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(bitboard::coordinates(square_index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let e4: BoardCoordinates = "e4".parse().unwrap();
+        let a8: BoardCoordinates = "a8".parse().unwrap();
+        let mut set: SquareSet = [e4, a8].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(e4));
+        assert!(!set.contains("d4".parse().unwrap()));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![a8, e4]);
+
+        set.remove(a8);
+        set.remove(e4);
+        assert!(set.is_empty());
+        assert_eq!(set, SquareSet::new());
+    }
+}
@@ -0,0 +1,302 @@
+//! Chess clocks. Every method that depends on the time takes the current instant, so the
+//! caller decides where time comes from and tests can replay any sequence of moves exactly.
+
+mod time_control;
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub use time_control::{Increment, ParseTimeControlError, Stage, TimeControl};
+
+use crate::prelude::*;
+
+/// Reason why the clock could not be pressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClockError {
+    NotRunning,
+    /// The player ran out of time before finishing the move.
+    FlagFallen(Player),
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRunning => write!(f, "the clock is not running"),
+            Self::FlagFallen(player) => write!(f, "{player} has run out of time"),
+        }
+    }
+}
+
+impl std::error::Error for ClockError {}
+
+/// Time left for both players under a [`TimeControl`]. Only the clock of the player to move
+/// runs, starting with white.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    time_control: TimeControl,
+    turn: Player,
+    /// Time left for each player, not counting the move in progress.
+    remaining: [Duration; 2],
+    /// Index into the stages of the time control, for each player.
+    stage: [usize; 2],
+    /// Moves played by each player since their current stage began.
+    stage_moves: [u32; 2],
+    /// When the clock was last started or resumed, if it is running.
+    running_since: Option<Instant>,
+    /// Time spent on the move in progress before the clock was last paused.
+    paused_elapsed: Duration,
+    flag_fallen: Option<Player>,
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl) -> Self {
+        let time = time_control.get_stages()[0].time;
+        Self {
+            time_control,
+            turn: Player::White,
+            remaining: [time; 2],
+            stage: [0; 2],
+            stage_moves: [0; 2],
+            running_since: None,
+            paused_elapsed: Duration::ZERO,
+            flag_fallen: None,
+        }
+    }
+
+    pub fn get_time_control(&self) -> &TimeControl {
+        &self.time_control
+    }
+
+    /// The player whose clock runs, or would run if the clock is paused.
+    pub fn get_turn(&self) -> Player {
+        self.turn
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Starts the clock of the player to move, or resumes it after a pause.
+    pub fn start(&mut self, now: Instant) {
+        if self.running_since.is_none() && self.flag_fallen.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// Stops both clocks, keeping the time already spent on the move in progress.
+    pub fn pause(&mut self, now: Instant) {
+        if self.running_since.is_some() {
+            self.paused_elapsed = self.elapsed(now);
+            self.running_since = None;
+        }
+    }
+
+    /// Ends the move of the player to move and starts the clock of the opponent.
+    ///
+    /// Fails if the player ran out of time before pressing, in which case the clock stops for
+    /// good.
+    pub fn press(&mut self, now: Instant) -> Result<(), ClockError> {
+        if let Some(player) = self.flag_fallen {
+            return Err(ClockError::FlagFallen(player));
+        }
+        if self.running_since.is_none() {
+            return Err(ClockError::NotRunning);
+        }
+
+        let elapsed = self.elapsed(now);
+        let player = self.turn as usize;
+        let stage = self.current_stage(self.turn);
+        let charged = match stage.increment {
+            Increment::Delay(delay) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        };
+        if charged >= self.remaining[player] {
+            self.remaining[player] = Duration::ZERO;
+            self.running_since = None;
+            self.flag_fallen = Some(self.turn);
+            return Err(ClockError::FlagFallen(self.turn));
+        }
+
+        self.remaining[player] -= charged;
+        self.remaining[player] += match stage.increment {
+            Increment::Fischer(increment) => increment,
+            Increment::Bronstein(delay) => delay.min(elapsed),
+            Increment::None | Increment::Delay(_) => Duration::ZERO,
+        };
+
+        // Finishing the moves of a stage brings the time of the next one
+        self.stage_moves[player] += 1;
+        if stage.moves == Some(self.stage_moves[player]) {
+            let stages = self.time_control.get_stages();
+            self.stage[player] = (self.stage[player] + 1).min(stages.len() - 1);
+            self.stage_moves[player] = 0;
+            self.remaining[player] += stages[self.stage[player]].time;
+        }
+
+        self.turn = match self.turn {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        };
+        self.running_since = Some(now);
+        self.paused_elapsed = Duration::ZERO;
+        Ok(())
+    }
+
+    /// Time `player` has left at `now`. During a simple delay the clock of the player to move
+    /// does not go down yet.
+    pub fn remaining_time(&self, player: Player, now: Instant) -> Duration {
+        let remaining = self.remaining[player as usize];
+        if player != self.turn {
+            return remaining;
+        }
+        let elapsed = self.elapsed(now);
+        let charged = match self.current_stage(player).increment {
+            Increment::Delay(delay) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        };
+        remaining.saturating_sub(charged)
+    }
+
+    /// The player who has run out of time, if any.
+    ///
+    /// Pass the result to [`GameState::time_out`] to end the game.
+    pub fn flag_fallen(&self, now: Instant) -> Option<Player> {
+        self.flag_fallen.or_else(|| {
+            (self.is_running() && self.remaining_time(self.turn, now).is_zero())
+                .then_some(self.turn)
+        })
+    }
+
+    /// Moves the player has left to play in their current stage, or `None` in a stage that
+    /// lasts for the rest of the game.
+    pub fn moves_to_go(&self, player: Player) -> Option<u32> {
+        let moves = self.current_stage(player).moves?;
+        Some(moves - self.stage_moves[player as usize])
+    }
+
+    fn current_stage(&self, player: Player) -> Stage {
+        self.time_control.get_stages()[self.stage[player as usize]]
+    }
+
+    /// Time spent on the move in progress.
+    fn elapsed(&self, now: Instant) -> Duration {
+        let running = match self.running_since {
+            Some(since) => now.saturating_duration_since(since),
+            None => Duration::ZERO,
+        };
+        self.paused_elapsed + running
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    /// Plays moves that took the given number of seconds each, starting with white.
+    fn play(clock: &mut Clock, start: Instant, seconds: &[u32]) -> Instant {
+        let mut now = start;
+        for seconds in seconds {
+            now += SECOND * *seconds;
+            clock.press(now).unwrap();
+        }
+        now
+    }
+
+    #[test]
+    fn sudden_death_and_fischer() {
+        let start = Instant::now();
+        let mut clock = Clock::new(TimeControl::sudden_death(SECOND * 60));
+        clock.start(start);
+        let now = play(&mut clock, start, &[10, 5]);
+        assert_eq!(clock.remaining_time(Player::White, now), SECOND * 50);
+        assert_eq!(clock.remaining_time(Player::Black, now), SECOND * 55);
+        assert_eq!(
+            clock.remaining_time(Player::White, now + SECOND * 20),
+            SECOND * 30
+        );
+
+        let mut clock = Clock::new(TimeControl::fischer(SECOND * 60, SECOND * 2));
+        clock.start(start);
+        let now = play(&mut clock, start, &[10, 1]);
+        assert_eq!(clock.remaining_time(Player::White, now), SECOND * 52);
+        assert_eq!(clock.remaining_time(Player::Black, now), SECOND * 61);
+    }
+
+    #[test]
+    fn bronstein_and_simple_delay() {
+        let start = Instant::now();
+        // Bronstein gives back at most the time the move took
+        let mut clock = Clock::new(TimeControl::bronstein(SECOND * 60, SECOND * 5));
+        clock.start(start);
+        let now = play(&mut clock, start, &[10, 2]);
+        assert_eq!(clock.remaining_time(Player::White, now), SECOND * 55);
+        assert_eq!(clock.remaining_time(Player::Black, now), SECOND * 60);
+
+        // A simple delay waits before counting down
+        let mut clock = Clock::new(TimeControl::simple_delay(SECOND * 60, SECOND * 5));
+        clock.start(start);
+        assert_eq!(
+            clock.remaining_time(Player::White, start + SECOND * 3),
+            SECOND * 60
+        );
+        let now = play(&mut clock, start, &[10, 2]);
+        assert_eq!(clock.remaining_time(Player::White, now), SECOND * 55);
+        assert_eq!(clock.remaining_time(Player::Black, now), SECOND * 60);
+    }
+
+    #[test]
+    fn multi_stage_controls() {
+        let time_control: TimeControl = "2/60+10:30".parse().unwrap();
+        assert_eq!(
+            time_control.get_stages()[0],
+            Stage {
+                moves: Some(2),
+                time: SECOND * 60,
+                increment: Increment::Fischer(SECOND * 10),
+            }
+        );
+        assert!("40/90+".parse::<TimeControl>().is_err());
+        assert!("0/60".parse::<TimeControl>().is_err());
+
+        let start = Instant::now();
+        let mut clock = Clock::new(time_control);
+        clock.start(start);
+        assert_eq!(clock.moves_to_go(Player::White), Some(2));
+        let now = play(&mut clock, start, &[20, 20, 20, 20]);
+        // Two moves of 20 seconds with 10 back each, then the second stage adds 30 seconds
+        assert_eq!(clock.remaining_time(Player::White, now), SECOND * 70);
+        assert_eq!(clock.moves_to_go(Player::White), None);
+
+        // The second stage has no increment
+        let now = play(&mut clock, now, &[20]);
+        assert_eq!(clock.remaining_time(Player::White, now), SECOND * 50);
+    }
+
+    #[test]
+    fn pausing_and_flag_fall() {
+        let start = Instant::now();
+        let mut clock = Clock::new(TimeControl::sudden_death(SECOND * 10));
+        assert_eq!(clock.press(start), Err(ClockError::NotRunning));
+
+        clock.start(start);
+        clock.pause(start + SECOND * 4);
+        assert_eq!(
+            clock.remaining_time(Player::White, start + SECOND * 100),
+            SECOND * 6
+        );
+        clock.start(start + SECOND * 100);
+        assert_eq!(clock.flag_fallen(start + SECOND * 105), None);
+        assert_eq!(clock.flag_fallen(start + SECOND * 106), Some(Player::White));
+        assert_eq!(
+            clock.press(start + SECOND * 107),
+            Err(ClockError::FlagFallen(Player::White))
+        );
+        assert_eq!(
+            ClockError::FlagFallen(Player::White).to_string(),
+            "White has run out of time"
+        );
+        assert!(!clock.is_running());
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Time a player gets back, or does not spend, on each move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Increment {
+    None,
+    /// Added to the clock after every move.
+    Fischer(Duration),
+    /// Added to the clock after every move, but never more than the move took.
+    Bronstein(Duration),
+    /// Waited out at the start of every move before the clock starts running down.
+    Delay(Duration),
+}
+
+/// One period of a time control.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Stage {
+    /// Moves to play within the stage, or `None` for the rest of the game.
+    pub moves: Option<u32>,
+    /// Time added to the clock when the stage begins.
+    pub time: Duration,
+    pub increment: Increment,
+}
+
+/// The stages a game is played under, such as 40 moves in 90 minutes followed by 30 minutes
+/// for the rest of the game, both with 30 seconds added per move.
+///
+/// Once every stage is done, the last one starts over.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeControl {
+    stages: Vec<Stage>,
+}
+
+impl TimeControl {
+    pub fn new(stages: Vec<Stage>) -> Self {
+        assert!(
+            !stages.is_empty(),
+            "a time control needs at least one stage"
+        );
+        Self { stages }
+    }
+
+    pub fn sudden_death(time: Duration) -> Self {
+        Self::single_stage(time, Increment::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        Self::single_stage(time, Increment::Fischer(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        Self::single_stage(time, Increment::Bronstein(delay))
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> Self {
+        Self::single_stage(time, Increment::Delay(delay))
+    }
+
+    fn single_stage(time: Duration, increment: Increment) -> Self {
+        Self::new(vec![Stage {
+            moves: None,
+            time,
+            increment,
+        }])
+    }
+
+    pub fn get_stages(&self) -> &[Stage] {
+        &self.stages
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = ParseTimeControlError;
+
+    /// Reads the notation of the PGN `TimeControl` tag, in seconds with stages separated by
+    /// `:`, so `40/5400+30:1800+30` is 40 moves in 90 minutes and then 30 minutes, with 30
+    /// seconds added per move.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || ParseTimeControlError(text.to_string());
+        let seconds = |field: &str| field.parse::<u64>().map(Duration::from_secs).ok();
+
+        let mut stages = Vec::new();
+        for stage in text.split(':') {
+            let (moves, rest) = match stage.split_once('/') {
+                Some((moves, rest)) => {
+                    let moves = moves.parse::<u32>().ok().filter(|moves| *moves > 0);
+                    (Some(moves.ok_or_else(error)?), rest)
+                }
+                None => (None, stage),
+            };
+            let (time, increment) = match rest.split_once('+') {
+                Some((time, increment)) => (
+                    seconds(time).ok_or_else(error)?,
+                    Increment::Fischer(seconds(increment).ok_or_else(error)?),
+                ),
+                None => (seconds(rest).ok_or_else(error)?, Increment::None),
+            };
+            stages.push(Stage {
+                moves,
+                time,
+                increment,
+            });
+        }
+        Ok(Self::new(stages))
+    }
+}
+
+/// The text given to [`TimeControl::from_str`] is not in the notation of the PGN
+/// `TimeControl` tag.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseTimeControlError(String);
+
+impl fmt::Display for ParseTimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a time control like `40/5400+30:1800+30`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseTimeControlError {}
@@ -7,5 +7,5 @@ mod square;
 pub use castling_rights::CastlingRights;
 pub use piece::Piece;
 pub use player::Player;
-pub use result::GameResult;
+pub use result::{GameResult, Termination};
 pub use square::Square;
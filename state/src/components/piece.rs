@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Piece {
     Pawn,
     Knight,
@@ -7,3 +9,18 @@ pub enum Piece {
     Queen,
     King,
 }
+
+impl fmt::Display for Piece {
+    /// Writes the name of the piece in lower case, such as "knight".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Pawn => "pawn",
+            Self::Knight => "knight",
+            Self::Bishop => "bishop",
+            Self::Rook => "rook",
+            Self::Queen => "queen",
+            Self::King => "king",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -1,5 +1,17 @@
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Player {
     White,
     Black,
 }
+
+impl fmt::Display for Player {
+    /// Writes the colour of the player, "White" or "Black".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::White => write!(f, "White"),
+            Self::Black => write!(f, "Black"),
+        }
+    }
+}
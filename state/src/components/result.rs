@@ -1,13 +1,30 @@
+use std::fmt;
+
+use super::Player;
+
+/// Why a game ended.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub enum GameResult {
+pub enum Termination {
     Checkmate,
     Stalemate,
     DeadPosition,
+    /// Claimed by a player when the same position occurs for the third time.
     ThreefoldRepetition,
+    /// Claimed by a player after fifty moves by each side without a capture or pawn move.
     FiftyMoveRule,
+    /// Ends the game without a claim when the same position occurs for the fifth time.
+    FivefoldRepetition,
+    /// Ends the game without a claim after seventy-five moves by each side without a capture
+    /// or pawn move.
+    SeventyFiveMoveRule,
+    Resignation,
+    Timeout,
+    DrawAgreement,
+    /// Decided by an arbiter or a program rather than on the board.
+    Adjudication,
 }
 
-impl GameResult {
+impl Termination {
     pub fn to_str(&self) -> &str {
         match self {
             Self::Checkmate => "Checkmate",
@@ -15,6 +32,65 @@ impl GameResult {
             Self::DeadPosition => "Dead Position",
             Self::ThreefoldRepetition => "Threefold Repetition",
             Self::FiftyMoveRule => "Fifty-move rule",
+            Self::FivefoldRepetition => "Fivefold Repetition",
+            Self::SeventyFiveMoveRule => "Seventy-five-move rule",
+            Self::Resignation => "Resignation",
+            Self::Timeout => "Timeout",
+            Self::DrawAgreement => "Draw agreement",
+            Self::Adjudication => "Adjudication",
+        }
+    }
+}
+
+/// How a game ended: who won, if anyone, and why.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct GameResult {
+    winner: Option<Player>,
+    termination: Termination,
+}
+
+impl GameResult {
+    pub fn win(winner: Player, termination: Termination) -> Self {
+        Self {
+            winner: Some(winner),
+            termination,
+        }
+    }
+
+    pub fn draw(termination: Termination) -> Self {
+        Self {
+            winner: None,
+            termination,
+        }
+    }
+
+    /// The player who won, or `None` for a draw.
+    pub fn get_winner(&self) -> Option<Player> {
+        self.winner
+    }
+
+    pub fn get_termination(&self) -> Termination {
+        self.termination
+    }
+
+    /// The result as written at the end of a PGN game: `1-0`, `0-1` or `1/2-1/2`.
+    pub fn pgn_result(&self) -> &'static str {
+        match self.winner {
+            Some(Player::White) => "1-0",
+            Some(Player::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    /// Describes the result for players, such as "White wins by checkmate".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = self.termination.to_str().to_lowercase();
+        match self.winner {
+            Some(Player::White) => write!(f, "White wins by {reason}"),
+            Some(Player::Black) => write!(f, "Black wins by {reason}"),
+            None => write!(f, "Draw by {reason}"),
         }
     }
 }
@@ -0,0 +1,358 @@
+mod tables;
+
+use std::ops::{AddAssign, Mul};
+
+use crate::bitboard::{self, Bitboard};
+use crate::prelude::*;
+
+const PIECES: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
+/// Middlegame and endgame halves of a score, blended by the remaining material at the end.
+#[derive(Copy, Clone, Default)]
+struct Score {
+    middlegame: i32,
+    endgame: i32,
+}
+
+impl Score {
+    fn new(middlegame: i32, endgame: i32) -> Self {
+        Self {
+            middlegame,
+            endgame,
+        }
+    }
+    /// Adds a score earned by `player` to a total kept from White's point of view.
+    fn add_for(&mut self, player: Player, other: Score) {
+        match player {
+            Player::White => *self += other,
+            Player::Black => *self += other * -1,
+        }
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Self) {
+        self.middlegame += other.middlegame;
+        self.endgame += other.endgame;
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Self;
+
+    fn mul(self, factor: i32) -> Self {
+        Self::new(self.middlegame * factor, self.endgame * factor)
+    }
+}
+
+/// Total phase of a position that still has all of its minor and major pieces.
+const MAX_PHASE: i32 = 24;
+
+const BISHOP_PAIR: Score = Score {
+    middlegame: 30,
+    endgame: 50,
+};
+const DOUBLED_PAWN: Score = Score {
+    middlegame: -10,
+    endgame: -20,
+};
+const ISOLATED_PAWN: Score = Score {
+    middlegame: -15,
+    endgame: -10,
+};
+/// Bonus for a passed pawn, indexed by how many ranks it has advanced from its starting rank.
+const PASSED_PAWN: [Score; 7] = [
+    Score {
+        middlegame: 0,
+        endgame: 0,
+    },
+    Score {
+        middlegame: 5,
+        endgame: 10,
+    },
+    Score {
+        middlegame: 10,
+        endgame: 20,
+    },
+    Score {
+        middlegame: 20,
+        endgame: 40,
+    },
+    Score {
+        middlegame: 35,
+        endgame: 70,
+    },
+    Score {
+        middlegame: 60,
+        endgame: 120,
+    },
+    Score {
+        middlegame: 100,
+        endgame: 200,
+    },
+];
+const SHIELD_PAWN_CLOSE: i32 = 10;
+const SHIELD_PAWN_FAR: i32 = 5;
+const OPEN_FILE_NEAR_KING: i32 = -15;
+
+/// Scores the position in centipawns from the point of view of the side to move.
+///
+/// The score adds up material, piece-square tables, mobility, pawn structure and king safety,
+/// each with a middlegame and an endgame weight that are blended by the remaining material.
+pub fn evaluate(game_state: &GameState) -> i32 {
+    evaluate_position(game_state.get_position())
+}
+
+/// Same as [`evaluate`], for a position on its own.
+pub fn evaluate_position(position: &Position) -> i32 {
+    let mut score = Score::default();
+    let mut phase = 0;
+
+    for player in [Player::White, Player::Black] {
+        for piece in PIECES {
+            let pieces = position.pieces_of(player, piece);
+            for square in bitboard::squares(pieces) {
+                let (row, col) = (square / 8, square % 8);
+                score.add_for(player, material(piece));
+                score.add_for(player, piece_square(player, piece, row, col));
+                score.add_for(player, mobility(position, player, piece, square));
+            }
+            let piece_phase = match piece {
+                Piece::Knight | Piece::Bishop => 1,
+                Piece::Rook => 2,
+                Piece::Queen => 4,
+                Piece::Pawn | Piece::King => 0,
+            };
+            phase += piece_phase * pieces.count_ones() as i32;
+        }
+
+        if position.pieces_of(player, Piece::Bishop).count_ones() >= 2 {
+            score.add_for(player, BISHOP_PAIR);
+        }
+        score.add_for(player, pawn_structure(position, player));
+        let king_location = match player {
+            Player::White => position.get_white_king_location(),
+            Player::Black => position.get_black_king_location(),
+        };
+        score.add_for(player, king_safety(position, player, king_location));
+    }
+
+    // Promotions can push the phase over its starting value
+    let phase = phase.min(MAX_PHASE);
+    let white_score = (score.middlegame * phase + score.endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+
+    match position.get_turn() {
+        Player::White => white_score,
+        Player::Black => -white_score,
+    }
+}
+
+/// Middlegame value of a piece in centipawns, for weighing captures against each other. The
+/// king has no value, since it is never captured.
+pub(crate) fn piece_value(piece: Piece) -> i32 {
+    material(piece).middlegame
+}
+
+fn material(piece: Piece) -> Score {
+    match piece {
+        Piece::Pawn => Score::new(100, 120),
+        Piece::Knight => Score::new(320, 300),
+        Piece::Bishop => Score::new(330, 320),
+        Piece::Rook => Score::new(500, 530),
+        Piece::Queen => Score::new(900, 950),
+        Piece::King => Score::new(0, 0),
+    }
+}
+
+fn piece_square(player: Player, piece: Piece, row: usize, col: usize) -> Score {
+    // Tables are written for White, so Black looks them up upside down
+    let row = match player {
+        Player::White => row,
+        Player::Black => 7 - row,
+    };
+    match piece {
+        Piece::Pawn => Score::new(
+            tables::PAWN_MIDDLEGAME[row][col],
+            tables::PAWN_ENDGAME[row][col],
+        ),
+        Piece::Knight => Score::new(tables::KNIGHT[row][col], tables::KNIGHT[row][col]),
+        Piece::Bishop => Score::new(tables::BISHOP[row][col], tables::BISHOP[row][col]),
+        Piece::Rook => Score::new(tables::ROOK[row][col], tables::ROOK[row][col]),
+        Piece::Queen => Score::new(tables::QUEEN[row][col], tables::QUEEN[row][col]),
+        Piece::King => Score::new(
+            tables::KING_MIDDLEGAME[row][col],
+            tables::KING_ENDGAME[row][col],
+        ),
+    }
+}
+
+/// Bonus for every square a piece can move to, relative to the mobility of an average piece.
+fn mobility(position: &Position, player: Player, piece: Piece, square: usize) -> Score {
+    let occupied = position.occupied();
+    let (attacks, average, weight) = match piece {
+        Piece::Knight => (bitboard::knight_attacks(square), 4, Score::new(4, 4)),
+        Piece::Bishop => (
+            bitboard::bishop_attacks(square, occupied),
+            7,
+            Score::new(5, 5),
+        ),
+        Piece::Rook => (
+            bitboard::rook_attacks(square, occupied),
+            7,
+            Score::new(2, 4),
+        ),
+        Piece::Queen => (
+            bitboard::queen_attacks(square, occupied),
+            14,
+            Score::new(1, 2),
+        ),
+        Piece::Pawn | Piece::King => return Score::default(),
+    };
+
+    let squares = (attacks & !position.occupied_by(player)).count_ones() as i32;
+    weight * (squares - average)
+}
+
+fn pawn_structure(position: &Position, player: Player) -> Score {
+    let enemy = match player {
+        Player::White => Player::Black,
+        Player::Black => Player::White,
+    };
+    let pawns = position.pieces_of(player, Piece::Pawn);
+    let enemy_pawns = position.pieces_of(enemy, Piece::Pawn);
+
+    let mut score = Score::default();
+    for col in 0..8 {
+        let pawns_on_file = (pawns & bitboard::file(col)).count_ones() as i32;
+        if pawns_on_file == 0 {
+            continue;
+        }
+        if pawns_on_file > 1 {
+            score += DOUBLED_PAWN * (pawns_on_file - 1);
+        }
+        if pawns & neighbouring_files(col) == 0 {
+            score += ISOLATED_PAWN * pawns_on_file;
+        }
+    }
+
+    for square in bitboard::squares(pawns) {
+        let (row, col) = (square / 8, square % 8);
+        // Rows in front of the pawn, from its point of view
+        let ahead = match player {
+            Player::White => (1 << (row * 8)) - 1,
+            Player::Black => u64::MAX.checked_shl((row as u32 + 1) * 8).unwrap_or(0),
+        };
+        let lanes = bitboard::file(col) | neighbouring_files(col);
+        if enemy_pawns & ahead & lanes == 0 {
+            let advanced = match player {
+                Player::White => 6 - row,
+                Player::Black => row - 1,
+            };
+            score += PASSED_PAWN[advanced];
+        }
+    }
+
+    score
+}
+
+/// Rewards pawns sheltering a castled king and punishes open files next to it. Only matters in
+/// the middlegame, since the king should come out once the heavy pieces are gone.
+fn king_safety(position: &Position, player: Player, king_location: BoardCoordinates) -> Score {
+    let (home_row, forward): (usize, isize) = match player {
+        Player::White => (7, -1),
+        Player::Black => (0, 1),
+    };
+    if king_location.row().abs_diff(home_row) > 1 {
+        return Score::default();
+    }
+
+    let pawns = position.pieces_of(player, Piece::Pawn);
+    let mut safety = 0;
+    let files = king_location.col().saturating_sub(1)..=(king_location.col() + 1).min(7);
+    for col in files {
+        for (distance, bonus) in [(1, SHIELD_PAWN_CLOSE), (2, SHIELD_PAWN_FAR)] {
+            let row = king_location.row() as isize + forward * distance;
+            if (0..8).contains(&row) && pawns & (1 << (row as usize * 8 + col)) != 0 {
+                safety += bonus;
+            }
+        }
+        if pawns & bitboard::file(col) == 0 {
+            safety += OPEN_FILE_NEAR_KING;
+        }
+    }
+
+    Score::new(safety, 0)
+}
+
+/// Every square on the files on either side of `col`.
+fn neighbouring_files(col: usize) -> Bitboard {
+    let left = if col > 0 { bitboard::file(col - 1) } else { 0 };
+    let right = if col < 7 { bitboard::file(col + 1) } else { 0 };
+    left | right
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, evaluate_position};
+    use crate::prelude::*;
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        evaluate(&GameState::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn initial_position_is_equal() {
+        assert_eq!(evaluate(&GameState::new()), 0);
+        assert_eq!(evaluate_position(&Position::new()), 0);
+    }
+
+    #[test]
+    fn mirrored_positions_score_the_same_for_the_side_to_move() {
+        let pairs = [
+            (
+                "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+                "rnbqkb1r/pppp1ppp/5n2/4p3/4P3/2N5/PPPP1PPP/R1BQKBNR b KQkq - 2 3",
+            ),
+            (
+                "6k1/5pp1/8/3P4/8/8/5PPP/6K1 w - - 0 1",
+                "6k1/5ppp/8/8/3p4/8/5PP1/6K1 b - - 0 1",
+            ),
+        ];
+        for (white, black) in pairs {
+            assert_eq!(evaluate_fen(white), evaluate_fen(black), "{white}");
+        }
+    }
+
+    #[test]
+    fn material_and_side_to_move() {
+        let white_up_a_rook = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+        assert!(evaluate_fen(white_up_a_rook) > 400);
+        assert!(evaluate_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1") < -400);
+    }
+
+    #[test]
+    fn pawn_structure_and_king_safety() {
+        // A far advanced passed pawn is worth more than one still at home
+        assert!(
+            evaluate_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1")
+                > evaluate_fen("4k3/8/8/8/8/8/P7/4K3 w - - 0 1")
+        );
+        // Doubled, isolated pawns are worse than connected ones
+        assert!(
+            evaluate_fen("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1")
+                < evaluate_fen("4k3/8/8/8/8/8/2PP4/4K3 w - - 0 1")
+        );
+        // A castled king behind its pawns is safer than one with the pawns pushed away
+        assert!(
+            evaluate_fen("r2q1rk1/ppp2ppp/8/8/8/8/PPP2PPP/R2Q1RK1 w - - 0 1")
+                > evaluate_fen("r2q1rk1/ppp2ppp/8/8/5PPP/8/PPP5/R2Q1RK1 w - - 0 1")
+        );
+    }
+}
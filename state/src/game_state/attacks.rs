@@ -0,0 +1,133 @@
+use super::move_generation::opponent;
+use crate::bitboard;
+use crate::prelude::*;
+
+impl Position {
+    /// Pieces of `player` attacking `square`, whoever stands on it. A piece attacks a square
+    /// even when moving there would leave its own king in check.
+    pub fn attackers_of(&self, square: BoardCoordinates, player: Player) -> SquareSet {
+        let square = bitboard::square_index(square);
+        SquareSet::from_bitboard(
+            self.attackers(square, self.occupied()) & self.occupancy[player as usize],
+        )
+    }
+
+    /// Pieces giving check to the king of the player to move.
+    pub fn checkers(&self) -> SquareSet {
+        self.attackers_of(self.king_location(self.turn), opponent(self.turn))
+    }
+
+    /// Pieces of `player` that cannot leave the line between their king and an enemy sliding
+    /// piece without exposing the king.
+    pub fn pinned_pieces(&self, player: Player) -> SquareSet {
+        let king = bitboard::square_index(self.king_location(player));
+        let occupied = self.occupied();
+        let own = self.occupancy[player as usize];
+
+        let mut pinned = 0;
+        for sniper in bitboard::squares(self.snipers(player)) {
+            let blockers = bitboard::between(king, sniper) & occupied;
+            if blockers.count_ones() == 1 && blockers & own != 0 {
+                pinned |= blockers;
+            }
+        }
+        SquareSet::from_bitboard(pinned)
+    }
+
+    /// Every square attacked by a piece of `player`, including squares of its own pieces that
+    /// it defends and squares a pawn could capture on if an enemy piece stood there.
+    pub fn attacked_squares(&self, player: Player) -> SquareSet {
+        let occupied = self.occupied();
+        let mut attacked = 0;
+        for piece in [
+            Piece::Pawn,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Rook,
+            Piece::Queen,
+            Piece::King,
+        ] {
+            for square in bitboard::squares(self.pieces_of(player, piece)) {
+                attacked |= match piece {
+                    Piece::Pawn => bitboard::pawn_attacks(player, square),
+                    Piece::Knight => bitboard::knight_attacks(square),
+                    Piece::Bishop => bitboard::bishop_attacks(square, occupied),
+                    Piece::Rook => bitboard::rook_attacks(square, occupied),
+                    Piece::Queen => bitboard::queen_attacks(square, occupied),
+                    Piece::King => bitboard::king_attacks(square),
+                };
+            }
+        }
+        SquareSet::from_bitboard(attacked)
+    }
+}
+
+/// The same questions about the current position of a game.
+impl GameState {
+    pub fn attackers_of(&self, square: BoardCoordinates, player: Player) -> SquareSet {
+        self.position.attackers_of(square, player)
+    }
+
+    pub fn checkers(&self) -> SquareSet {
+        self.position.checkers()
+    }
+
+    pub fn pinned_pieces(&self, player: Player) -> SquareSet {
+        self.position.pinned_pieces(player)
+    }
+
+    pub fn attacked_squares(&self, player: Player) -> SquareSet {
+        self.position.attacked_squares(player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn squares(names: &[&str]) -> SquareSet {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn attackers_and_checkers() {
+        let position = Position::from_fen("4k3/8/5n2/8/1b5R/8/5N2/4K3 w - - 0 1").unwrap();
+        let e4 = "e4".parse().unwrap();
+        assert_eq!(
+            position.attackers_of(e4, Player::White),
+            squares(&["f2", "h4"])
+        );
+        assert_eq!(position.attackers_of(e4, Player::Black), squares(&["f6"]));
+        assert_eq!(position.checkers(), squares(&["b4"]));
+        assert!(Position::new().checkers().is_empty());
+
+        let game_state = GameState::from_position(position);
+        assert_eq!(game_state.checkers(), squares(&["b4"]));
+        assert_eq!(game_state.attackers_of(e4, Player::Black), squares(&["f6"]));
+    }
+
+    #[test]
+    fn pinned_pieces() {
+        // The queen pins the pawn on e2, while the rook behind it and the bishop with two
+        // pieces in its way pin nothing
+        let position = Position::from_fen("4r1k1/4q3/8/b7/8/2P5/3NP3/4K3 w - - 0 1").unwrap();
+        assert_eq!(position.pinned_pieces(Player::White), squares(&["e2"]));
+        assert!(position.pinned_pieces(Player::Black).is_empty());
+    }
+
+    #[test]
+    fn attacked_squares() {
+        let attacked = Position::new().attacked_squares(Player::White);
+        assert_eq!(attacked.len(), 22);
+        assert!(attacked.contains("e3".parse().unwrap()));
+        assert!(attacked.contains("e1".parse().unwrap()));
+        assert!(!attacked.contains("a1".parse().unwrap()));
+        assert!(!attacked.contains("e4".parse().unwrap()));
+
+        // The rook reaches the far end of the file, but stops at its own king
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let attacked = position.attacked_squares(Player::White);
+        assert!(attacked.contains("a8".parse().unwrap()));
+        assert!(!attacked.contains("g1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,359 @@
+use std::fmt;
+
+use super::move_generation::opponent;
+use crate::prelude::*;
+
+/// Reason why a [`PositionBuilder`] does not describe a position that can occur in a game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PositionError {
+    /// Every player needs exactly one king.
+    KingCount {
+        player: Player,
+        count: usize,
+    },
+    PawnOnBackRank(BoardCoordinates),
+    /// The player who just moved cannot have left their own king in check.
+    OpponentInCheck,
+    /// The king or the rook of a castling right is not on its original square.
+    InvalidCastlingRights {
+        player: Player,
+        king_side: bool,
+    },
+    /// The square was not skipped by a pawn of the opponent that just advanced two squares.
+    InvalidEnPassant(BoardCoordinates),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KingCount { player, count } => {
+                write!(f, "{player} has {count} kings instead of one")
+            }
+            Self::PawnOnBackRank(square) => write!(f, "a pawn stands on {square}"),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::InvalidCastlingRights { player, king_side } => {
+                let side = if *king_side { "king" } else { "queen" };
+                write!(
+                    f,
+                    "{player} cannot castle {side} side without king and rook on their \
+                     original squares"
+                )
+            }
+            Self::InvalidEnPassant(square) => {
+                write!(f, "no pawn can have just skipped {square}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+/// Sets up a position piece by piece, and checks that it is legal before turning it into a
+/// [`GameState`].
+///
+/// A new builder has an empty board with white to move and no castling rights.
+#[derive(Clone, Debug)]
+pub struct PositionBuilder {
+    board: [[Square; 8]; 8],
+    turn: Player,
+    castling_rights: CastlingRights,
+    en_passant_square: Option<BoardCoordinates>,
+    halfmove_clock: u16,
+    fullmove_number: u128,
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&Position> for PositionBuilder {
+    /// Starts from an existing position, for example to edit it.
+    fn from(position: &Position) -> Self {
+        Self {
+            board: position.board,
+            turn: position.turn,
+            castling_rights: position.castling_rights,
+            en_passant_square: position.en_passant_square,
+            halfmove_clock: position.move_counter.get_fifty_move_rule_count(),
+            fullmove_number: position.move_counter.get_fullmove_count(),
+        }
+    }
+}
+
+impl From<&GameState> for PositionBuilder {
+    /// Starts from the current position of a game.
+    fn from(game_state: &GameState) -> Self {
+        Self::from(game_state.get_position())
+    }
+}
+
+impl PositionBuilder {
+    pub fn new() -> Self {
+        Self {
+            board: [[Square::Empty; 8]; 8],
+            turn: Player::White,
+            castling_rights: CastlingRights::new(false, false, false, false),
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// Puts a piece on `coordinates`, or clears it with [`Square::Empty`].
+    pub fn square(&mut self, coordinates: BoardCoordinates, square: Square) -> &mut Self {
+        self.board[coordinates.row()][coordinates.col()] = square;
+        self
+    }
+
+    pub fn turn(&mut self, turn: Player) -> &mut Self {
+        self.turn = turn;
+        self
+    }
+
+    pub fn castling_rights(&mut self, castling_rights: CastlingRights) -> &mut Self {
+        self.castling_rights = castling_rights;
+        self
+    }
+
+    pub fn en_passant_square(&mut self, en_passant_square: Option<BoardCoordinates>) -> &mut Self {
+        self.en_passant_square = en_passant_square;
+        self
+    }
+
+    /// Sets the halfmove clock of the fifty-move rule and the fullmove number, which starts
+    /// at `1`.
+    pub fn move_counts(&mut self, halfmove_clock: u16, fullmove_number: u128) -> &mut Self {
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+        self
+    }
+
+    /// Checks the position and builds a game starting from it.
+    pub fn build(&self) -> Result<GameState, PositionError> {
+        self.build_position().map(GameState::from_position)
+    }
+
+    /// Checks the position and builds it, without a game around it.
+    pub fn build_position(&self) -> Result<Position, PositionError> {
+        self.validate_pieces()?;
+        self.validate_castling_rights()?;
+        self.validate_en_passant()?;
+
+        let position = Position::from_parts(
+            self.board,
+            self.turn,
+            self.castling_rights,
+            self.en_passant_square,
+            MoveCounter::from_counts(self.halfmove_clock, self.fullmove_number, self.turn),
+        );
+        let opponent = opponent(self.turn);
+        if !position
+            .attackers_of(position.king_location(opponent), self.turn)
+            .is_empty()
+        {
+            return Err(PositionError::OpponentInCheck);
+        }
+        Ok(position)
+    }
+
+    fn validate_pieces(&self) -> Result<(), PositionError> {
+        for player in [Player::White, Player::Black] {
+            let count = self
+                .board
+                .iter()
+                .flatten()
+                .filter(|square| **square == Square::Occupied(player, Piece::King))
+                .count();
+            if count != 1 {
+                return Err(PositionError::KingCount { player, count });
+            }
+        }
+
+        for row in [0, 7] {
+            for col in 0..8 {
+                if matches!(self.board[row][col], Square::Occupied(_, Piece::Pawn)) {
+                    return Err(PositionError::PawnOnBackRank(BoardCoordinates::new(
+                        row, col,
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_castling_rights(&self) -> Result<(), PositionError> {
+        let rights = self.castling_rights;
+        for (right, player, king_side) in [
+            (rights.get_white_king_side(), Player::White, true),
+            (rights.get_white_queen_side(), Player::White, false),
+            (rights.get_black_king_side(), Player::Black, true),
+            (rights.get_black_queen_side(), Player::Black, false),
+        ] {
+            let row = match player {
+                Player::White => 7,
+                Player::Black => 0,
+            };
+            let rook_col = if king_side { 7 } else { 0 };
+            if right
+                && (self.board[row][4] != Square::Occupied(player, Piece::King)
+                    || self.board[row][rook_col] != Square::Occupied(player, Piece::Rook))
+            {
+                return Err(PositionError::InvalidCastlingRights { player, king_side });
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_en_passant(&self) -> Result<(), PositionError> {
+        let Some(square) = self.en_passant_square else {
+            return Ok(());
+        };
+        let (row, col) = (square.row(), square.col());
+        // The square skipped by a double pawn push is on the sixth rank from the mover's side,
+        // with the pawn in front of it and nothing left on the square it started from
+        let (pawn_row, start_row) = match (self.turn, row) {
+            (Player::White, 2) => (3, 1),
+            (Player::Black, 5) => (4, 6),
+            _ => return Err(PositionError::InvalidEnPassant(square)),
+        };
+        if self.board[row][col] != Square::Empty
+            || self.board[start_row][col] != Square::Empty
+            || self.board[pawn_row][col] != Square::Occupied(opponent(self.turn), Piece::Pawn)
+        {
+            return Err(PositionError::InvalidEnPassant(square));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn coordinates(name: &str) -> BoardCoordinates {
+        name.parse().unwrap()
+    }
+
+    fn kings() -> PositionBuilder {
+        let mut builder = PositionBuilder::new();
+        builder
+            .square(
+                coordinates("e1"),
+                Square::Occupied(Player::White, Piece::King),
+            )
+            .square(
+                coordinates("e8"),
+                Square::Occupied(Player::Black, Piece::King),
+            );
+        builder
+    }
+
+    #[test]
+    fn builds_a_consistent_game() {
+        let mut builder = kings();
+        builder
+            .square(
+                coordinates("h1"),
+                Square::Occupied(Player::White, Piece::Rook),
+            )
+            .square(
+                coordinates("d7"),
+                Square::Occupied(Player::Black, Piece::Queen),
+            )
+            .turn(Player::Black)
+            .castling_rights(CastlingRights::new(true, false, false, false))
+            .move_counts(3, 20);
+        let game_state = builder.build().unwrap();
+        assert_eq!(game_state.to_fen(), "4k3/3q4/8/8/8/8/8/4K2R b K - 3 20");
+        assert_eq!(game_state.get_white_king_location(), coordinates("e1"));
+        assert!(!game_state.get_is_check());
+        assert!(!game_state.is_game_over());
+
+        // Editing a game keeps everything that is not changed
+        let mut builder = PositionBuilder::from(&GameState::new());
+        builder.square(coordinates("e2"), Square::Empty);
+        let game_state = builder.build().unwrap();
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(game_state.get_valid_moves().len(), 29);
+    }
+
+    #[test]
+    fn rejects_illegal_positions() {
+        let mut builder = kings();
+        builder.square(coordinates("e8"), Square::Empty);
+        assert_eq!(
+            builder.build().err(),
+            Some(PositionError::KingCount {
+                player: Player::Black,
+                count: 0,
+            })
+        );
+
+        let mut builder = kings();
+        builder.square(
+            coordinates("a8"),
+            Square::Occupied(Player::Black, Piece::Pawn),
+        );
+        assert_eq!(
+            builder.build().err(),
+            Some(PositionError::PawnOnBackRank(coordinates("a8")))
+        );
+
+        // White to move while the black king is attacked by the rook
+        let mut builder = kings();
+        builder.square(
+            coordinates("e4"),
+            Square::Occupied(Player::White, Piece::Rook),
+        );
+        assert_eq!(builder.build().err(), Some(PositionError::OpponentInCheck));
+        builder.turn(Player::Black);
+        assert!(builder.build().unwrap().get_is_check());
+
+        let mut builder = kings();
+        builder.castling_rights(CastlingRights::new(false, false, false, true));
+        assert_eq!(
+            builder.build().err(),
+            Some(PositionError::InvalidCastlingRights {
+                player: Player::Black,
+                king_side: false,
+            })
+        );
+        assert_eq!(
+            builder.build().unwrap_err().to_string(),
+            "Black cannot castle queen side without king and rook on their original squares"
+        );
+    }
+
+    #[test]
+    fn checks_the_en_passant_square() {
+        let mut builder = kings();
+        builder
+            .square(
+                coordinates("d5"),
+                Square::Occupied(Player::Black, Piece::Pawn),
+            )
+            .en_passant_square(Some(coordinates("d6")));
+        assert!(builder.build().is_ok());
+
+        builder.turn(Player::Black);
+        assert_eq!(
+            builder.build().err(),
+            Some(PositionError::InvalidEnPassant(coordinates("d6")))
+        );
+
+        // The pawn cannot have come from d7 if another piece stands there
+        builder.turn(Player::White).square(
+            coordinates("d7"),
+            Square::Occupied(Player::Black, Piece::Bishop),
+        );
+        assert_eq!(
+            builder.build().err(),
+            Some(PositionError::InvalidEnPassant(coordinates("d6")))
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use super::move_generation::opponent;
+use crate::bitboard;
+use crate::eval::piece_value;
+use crate::prelude::*;
+
+/// Pieces in the order they are sent into an exchange, least valuable first.
+const EXCHANGE_ORDER: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
+impl Position {
+    /// Static exchange evaluation: the material `to_move` wins or loses, in centipawns, once
+    /// both players have made every capture on its end square that pays off for them.
+    ///
+    /// Each side captures with its least valuable piece first, and sliding pieces lined up
+    /// behind others join in as the squares in front of them empty. Pins and checks are not
+    /// taken into account, except that a king never captures a defended piece.
+    pub fn see(&self, to_move: &Move) -> i32 {
+        let start = bitboard::square_index(to_move.start);
+        let target = bitboard::square_index(to_move.end);
+        let mut occupied = self.occupied() & !(1 << start);
+
+        // Gains of the side to move after every capture in the sequence, as if it ended there
+        let mut gains = Vec::with_capacity(32);
+        let mut gain = match to_move.piece_captured {
+            Square::Occupied(_, piece) => piece_value(piece),
+            Square::Empty => 0,
+        };
+        let mut on_target = match to_move.piece_moved {
+            Square::Occupied(_, piece) => piece,
+            Square::Empty => return 0,
+        };
+        match to_move.special_move {
+            Some(SpecialMove::EnPassant) => {
+                let captured = (start / 8) * 8 + target % 8;
+                occupied &= !(1 << captured);
+                gain = piece_value(Piece::Pawn);
+            }
+            Some(SpecialMove::PawnPromotion(Square::Occupied(_, promoted))) => {
+                gain += piece_value(promoted) - piece_value(Piece::Pawn);
+                on_target = promoted;
+            }
+            _ => {}
+        }
+        gains.push(gain);
+
+        let mut side = opponent(self.turn);
+        loop {
+            let attackers = self.attackers(target, occupied) & occupied;
+            let own = attackers & self.occupancy[side as usize];
+            let Some((piece, square)) = EXCHANGE_ORDER.into_iter().find_map(|piece| {
+                let square = bitboard::squares(own & self.pieces_of(side, piece)).next()?;
+                Some((piece, square))
+            }) else {
+                break;
+            };
+            if piece == Piece::King && attackers & self.occupancy[opponent(side) as usize] != 0 {
+                break;
+            }
+
+            let mut gain = piece_value(on_target);
+            on_target = piece;
+            // A pawn taking back on the last rank comes back as a queen
+            if piece == Piece::Pawn && matches!(target / 8, 0 | 7) {
+                gain += piece_value(Piece::Queen) - piece_value(Piece::Pawn);
+                on_target = Piece::Queen;
+            }
+            gains.push(gain - gains[gains.len() - 1]);
+            occupied &= !(1 << square);
+            side = opponent(side);
+        }
+
+        // Either side may stop capturing when going on would lose more
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap_or_default();
+            let previous = gains.len() - 1;
+            gains[previous] = -(-gains[previous]).max(last);
+        }
+        gains[0]
+    }
+}
+
+impl GameState {
+    pub fn see(&self, to_move: &Move) -> i32 {
+        self.position.see(to_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn see(fen: &str, uci: &str) -> i32 {
+        let game_state = GameState::from_fen(fen).unwrap();
+        let to_move = game_state.parse_uci_move(uci).unwrap();
+        game_state.see(&to_move)
+    }
+
+    #[test]
+    fn exchanges() {
+        // An undefended pawn, and a pawn defended by a pawn
+        assert_eq!(see("1k6/8/8/4p3/8/8/8/1K2R3 w - - 0 1", "e1e5"), 100);
+        assert_eq!(see("1k6/8/3p4/4p3/8/8/8/1K2Q3 w - - 0 1", "e1e5"), -800);
+        // Knight takes a defended pawn, and the rook behind the first one backs it up
+        assert_eq!(
+            see("1k1r4/3r4/8/3p4/8/4N3/8/1K1R4 w - - 0 1", "e3d5"),
+            100 - 320
+        );
+        assert_eq!(see("1k1r4/8/8/3p4/8/4N3/3R4/1K1R4 w - - 0 1", "e3d5"), 100);
+        // A king only takes back when nothing defends the piece
+        assert_eq!(see("8/8/8/7Q/8/3k4/4p3/4K3 w - - 0 1", "h5e2"), 100);
+        assert_eq!(see("8/8/8/7Q/8/3k4/4p3/6K1 w - - 0 1", "h5e2"), -800);
+        // Quiet moves onto attacked squares lose the piece
+        assert_eq!(see("1k6/8/8/8/8/2p5/8/1K1R4 w - - 0 1", "d1d2"), -500);
+        // En passant and promotions
+        assert_eq!(see("1k6/8/8/3pP3/8/8/8/1K6 w - d6 0 1", "e5d6"), 100);
+        assert_eq!(see("6k1/P7/8/8/8/8/8/1K6 w - - 0 1", "a7a8q"), 800);
+        // The pawn taking back on the first rank promotes as well
+        assert_eq!(
+            see("k7/8/8/8/8/8/1p2K3/n6R w - - 0 1", "h1a1"),
+            320 - 500 - 800
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use std::fmt;
+
+use crate::prelude::*;
+
+/// Reason why a FEN string could not be turned into a [`GameState`].
+///
+/// Every variant carries the text of the field that failed, except for positions that no
+/// single field is to blame for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    InvalidPiecePlacement(String),
+    InvalidActiveColor(String),
+    InvalidCastlingRights(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    IllegalPosition(PositionError),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongFieldCount(count) => {
+                write!(f, "expected 4 or 6 space separated fields, found {count}")
+            }
+            Self::InvalidPiecePlacement(field) => write!(f, "invalid piece placement `{field}`"),
+            Self::InvalidActiveColor(field) => write!(f, "invalid active color `{field}`"),
+            Self::InvalidCastlingRights(field) => write!(f, "invalid castling rights `{field}`"),
+            Self::InvalidEnPassant(field) => write!(f, "invalid en passant square `{field}`"),
+            Self::InvalidHalfmoveClock(field) => write!(f, "invalid halfmove clock `{field}`"),
+            Self::InvalidFullmoveNumber(field) => write!(f, "invalid fullmove number `{field}`"),
+            Self::IllegalPosition(error) => write!(f, "illegal position: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl GameState {
+    /// Builds a game from a position in Forsyth-Edwards Notation.
+    ///
+    /// The halfmove clock and fullmove number may be left out, in which case they default to
+    /// `0` and `1`.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Position::from_fen(fen).map(Self::from_position)
+    }
+}
+
+impl Position {
+    /// Reads a position in Forsyth-Edwards Notation, like [`GameState::from_fen`].
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+
+        let board = parse_piece_placement(fields[0])?;
+        let turn = match fields[1] {
+            "w" => Player::White,
+            "b" => Player::Black,
+            other => return Err(FenError::InvalidActiveColor(other.to_string())),
+        };
+        let castling_rights = parse_castling_rights(fields[2])?;
+        let en_passant_square = match fields[3] {
+            "-" => None,
+            field => Some(
+                field
+                    .parse::<BoardCoordinates>()
+                    .map_err(|_| FenError::InvalidEnPassant(field.to_string()))?,
+            ),
+        };
+
+        let (fifty_move_rule_count, fullmove) = if fields.len() == 6 {
+            let halfmove_clock = fields[4]
+                .parse::<u16>()
+                .map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+            let fullmove = fields[5]
+                .parse::<u128>()
+                .ok()
+                .filter(|fullmove| *fullmove > 0)
+                .ok_or_else(|| FenError::InvalidFullmoveNumber(fields[5].to_string()))?;
+            (halfmove_clock, fullmove)
+        } else {
+            (0, 1)
+        };
+
+        let mut builder = PositionBuilder::new();
+        for (row, rank) in board.iter().enumerate() {
+            for (col, square) in rank.iter().enumerate() {
+                builder.square(BoardCoordinates::new(row, col), *square);
+            }
+        }
+        builder
+            .turn(turn)
+            .castling_rights(castling_rights)
+            .en_passant_square(en_passant_square)
+            .move_counts(fifty_move_rule_count, fullmove);
+
+        builder.build_position().map_err(|error| match error {
+            PositionError::KingCount { .. } | PositionError::PawnOnBackRank(_) => {
+                FenError::InvalidPiecePlacement(fields[0].to_string())
+            }
+            PositionError::InvalidCastlingRights { .. } => {
+                FenError::InvalidCastlingRights(fields[2].to_string())
+            }
+            PositionError::InvalidEnPassant(_) => FenError::InvalidEnPassant(fields[3].to_string()),
+            PositionError::OpponentInCheck => FenError::IllegalPosition(error),
+        })
+    }
+
+    /// Describes the position in Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for (row, rank) in self.board.iter().enumerate() {
+            let mut empty_squares = 0;
+            for square in rank {
+                match square {
+                    Square::Empty => empty_squares += 1,
+                    Square::Occupied(player, piece) => {
+                        if empty_squares > 0 {
+                            placement.push_str(&empty_squares.to_string());
+                            empty_squares = 0;
+                        }
+                        placement.push(piece_to_char(*player, *piece));
+                    }
+                }
+            }
+            if empty_squares > 0 {
+                placement.push_str(&empty_squares.to_string());
+            }
+            if row < 7 {
+                placement.push('/');
+            }
+        }
+
+        let turn = match self.turn {
+            Player::White => "w",
+            Player::Black => "b",
+        };
+
+        let castling_rights = self.castling_rights;
+        let mut castling = String::new();
+        if castling_rights.get_white_king_side() {
+            castling.push('K');
+        }
+        if castling_rights.get_white_queen_side() {
+            castling.push('Q');
+        }
+        if castling_rights.get_black_king_side() {
+            castling.push('k');
+        }
+        if castling_rights.get_black_queen_side() {
+            castling.push('q');
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant_square {
+            Some(coordinates) => coordinates.to_string(),
+            None => String::from("-"),
+        };
+
+        format!(
+            "{placement} {turn} {castling} {en_passant} {} {}",
+            self.move_counter.get_fifty_move_rule_count(),
+            self.move_counter.get_fullmove_count()
+        )
+    }
+}
+
+fn piece_to_char(player: Player, piece: Piece) -> char {
+    let c = match piece {
+        Piece::Pawn => 'p',
+        Piece::Knight => 'n',
+        Piece::Bishop => 'b',
+        Piece::Rook => 'r',
+        Piece::Queen => 'q',
+        Piece::King => 'k',
+    };
+    match player {
+        Player::White => c.to_ascii_uppercase(),
+        Player::Black => c,
+    }
+}
+
+fn char_to_square(c: char) -> Option<Square> {
+    let player = if c.is_ascii_uppercase() {
+        Player::White
+    } else {
+        Player::Black
+    };
+    let piece = match c.to_ascii_lowercase() {
+        'p' => Piece::Pawn,
+        'n' => Piece::Knight,
+        'b' => Piece::Bishop,
+        'r' => Piece::Rook,
+        'q' => Piece::Queen,
+        'k' => Piece::King,
+        _ => return None,
+    };
+    Some(Square::Occupied(player, piece))
+}
+
+fn parse_piece_placement(field: &str) -> Result<[[Square; 8]; 8], FenError> {
+    let error = || FenError::InvalidPiecePlacement(field.to_string());
+
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(error());
+    }
+
+    let mut board = [[Square::Empty; 8]; 8];
+    for (row, rank) in ranks.iter().enumerate() {
+        let mut col = 0;
+        for c in rank.chars() {
+            if let Some(empty_squares) = c.to_digit(10) {
+                if !(1..=8).contains(&empty_squares) {
+                    return Err(error());
+                }
+                col += empty_squares as usize;
+            } else {
+                let square = char_to_square(c).ok_or_else(error)?;
+                if col > 7 {
+                    return Err(error());
+                }
+                board[row][col] = square;
+                col += 1;
+            }
+            if col > 8 {
+                return Err(error());
+            }
+        }
+        if col != 8 {
+            return Err(error());
+        }
+    }
+
+    Ok(board)
+}
+
+fn parse_castling_rights(field: &str) -> Result<CastlingRights, FenError> {
+    let error = || FenError::InvalidCastlingRights(field.to_string());
+
+    if field == "-" {
+        return Ok(CastlingRights::new(false, false, false, false));
+    }
+
+    let mut rights = [false; 4];
+    for c in field.chars() {
+        let index = match c {
+            'K' => 0,
+            'Q' => 1,
+            'k' => 2,
+            'q' => 3,
+            _ => return Err(error()),
+        };
+        if rights[index] {
+            return Err(error());
+        }
+        rights[index] = true;
+    }
+
+    Ok(CastlingRights::new(
+        rights[0], rights[1], rights[2], rights[3],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FenError;
+    use crate::prelude::*;
+
+    const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn initial_position_round_trip() {
+        assert_eq!(GameState::new().to_fen(), STARTING_FEN);
+
+        let game_state = GameState::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game_state.to_fen(), STARTING_FEN);
+        assert_eq!(game_state.get_valid_moves().len(), 20);
+    }
+
+    #[test]
+    fn tracks_every_field() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let game_state = GameState::from_fen(fen).unwrap();
+        assert_eq!(game_state.to_fen(), fen);
+        assert_eq!(game_state.get_valid_moves().len(), 48);
+
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        let game_state = GameState::from_fen(fen).unwrap();
+        assert_eq!(game_state.to_fen(), fen);
+        assert_eq!(
+            game_state.get_en_passant_square(),
+            Some(BoardCoordinates::new(2, 5))
+        );
+        assert_eq!(
+            game_state.get_white_king_location(),
+            BoardCoordinates::new(7, 4)
+        );
+
+        let fen = "8/8/4k3/8/2K5/8/8/8 b - - 37 80";
+        let game_state = GameState::from_fen(fen).unwrap();
+        assert_eq!(game_state.to_fen(), fen);
+        assert_eq!(game_state.get_turn(), Player::Black);
+        assert_eq!(
+            game_state.get_black_king_location(),
+            BoardCoordinates::new(2, 4)
+        );
+    }
+
+    #[test]
+    fn counters_follow_moves() {
+        let mut game_state = GameState::new();
+        let e4 = Move::new(
+            BoardCoordinates::new(6, 4),
+            BoardCoordinates::new(4, 4),
+            None,
+            game_state.get_position(),
+        );
+        game_state.make_new_move(e4);
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        let nf6 = Move::new(
+            BoardCoordinates::new(0, 6),
+            BoardCoordinates::new(2, 5),
+            None,
+            game_state.get_position(),
+        );
+        game_state.make_new_move(nf6);
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+        );
+
+        game_state.undo_last_move();
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        game_state.undo_last_move();
+        assert_eq!(game_state.to_fen(), STARTING_FEN);
+    }
+
+    #[test]
+    fn missing_counters_use_defaults() {
+        let game_state = GameState::from_fen("8/8/4k3/8/2K5/8/8/8 w - -").unwrap();
+        assert_eq!(game_state.to_fen(), "8/8/4k3/8/2K5/8/8/8 w - - 0 1");
+    }
+
+    #[test]
+    fn long_halfmove_clocks() {
+        let fen = "8/8/4k3/8/2K5/8/8/7R w - - 300 200";
+        let mut game_state = GameState::from_fen(fen).unwrap();
+        assert_eq!(game_state.to_fen(), fen);
+
+        game_state.make_new_move(game_state.parse_uci_move("h1h2").unwrap());
+        assert_eq!(game_state.to_fen(), "8/8/4k3/8/2K5/8/7R/8 b - - 301 200");
+        assert_eq!(
+            game_state.get_game_result(),
+            Some(GameResult::draw(Termination::SeventyFiveMoveRule))
+        );
+    }
+
+    #[test]
+    fn reports_the_failing_field() {
+        let cases = [
+            ("8/8/8/8/8/8/8/8 w", FenError::WrongFieldCount(2)),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+                FenError::InvalidPiecePlacement("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP".to_string()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                FenError::InvalidPiecePlacement(
+                    "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR".to_string(),
+                ),
+            ),
+            (
+                "8/8/8/8/8/8/8/K7 w - - 0 1",
+                FenError::InvalidPiecePlacement("8/8/8/8/8/8/8/K7".to_string()),
+            ),
+            (
+                "P3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+                FenError::InvalidPiecePlacement("P3k3/8/8/8/8/8/8/4K3".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+                FenError::InvalidActiveColor("x".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w KQ - 0 1",
+                FenError::InvalidCastlingRights("KQ".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e3 0 1",
+                FenError::InvalidEnPassant("e3".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/r3K3 b - - 0 1",
+                FenError::IllegalPosition(PositionError::OpponentInCheck),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+                FenError::InvalidHalfmoveClock("x".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+                FenError::InvalidFullmoveNumber("0".to_string()),
+            ),
+        ];
+        for (fen, error) in cases {
+            assert_eq!(GameState::from_fen(fen).err(), Some(error), "{fen}");
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use std::fmt;
+
+use super::move_generation::opponent;
+use crate::bitboard::{self, Bitboard};
+use crate::prelude::*;
+
+/// Reason why there is no legal move between two squares, as given by
+/// [`GameState::explain_illegal_move`] and [`GameState::try_make_move`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IllegalMoveError {
+    GameOver(GameResult),
+    NoPiece(BoardCoordinates),
+    WrongSide(BoardCoordinates),
+    /// The piece does not move that way.
+    InvalidMovement {
+        start: BoardCoordinates,
+        end: BoardCoordinates,
+    },
+    /// The piece on this square stands in the way, or on the destination itself.
+    PathBlocked(BoardCoordinates),
+    /// The piece may only move along the line between its king and the enemy piece on this
+    /// square.
+    Pinned {
+        pinner: BoardCoordinates,
+    },
+    LeavesKingInCheck,
+    /// The king or the rook has moved, or the rook was captured, with the given move. `None`
+    /// when the right was already gone when the game was set up.
+    CastlingRightsLost {
+        lost_by: Option<Move>,
+    },
+    /// The king would castle out of, through or into check on this square.
+    CastlingThroughCheck(BoardCoordinates),
+    MissingPromotion,
+    /// Either the move is not a promotion, or the piece is not one a pawn can become.
+    InvalidPromotion(Piece),
+}
+
+impl fmt::Display for IllegalMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GameOver(result) => write!(f, "the game is over: {result}"),
+            Self::NoPiece(start) => write!(f, "there is no piece on {start}"),
+            Self::WrongSide(start) => write!(f, "the piece on {start} belongs to the opponent"),
+            Self::InvalidMovement { start, end } => {
+                write!(f, "the piece on {start} cannot move to {end}")
+            }
+            Self::PathBlocked(square) => write!(f, "the piece on {square} is in the way"),
+            Self::Pinned { pinner } => {
+                write!(
+                    f,
+                    "the piece is pinned to its king by the piece on {pinner}"
+                )
+            }
+            Self::LeavesKingInCheck => write!(f, "the move would leave the king in check"),
+            Self::CastlingRightsLost {
+                lost_by: Some(lost_by),
+            } => write!(
+                f,
+                "castling on that side was ruled out by {}",
+                lost_by.to_uci()
+            ),
+            Self::CastlingRightsLost { lost_by: None } => {
+                write!(f, "castling on that side was not allowed from the start")
+            }
+            Self::CastlingThroughCheck(square) => {
+                write!(f, "the king cannot castle since {square} is attacked")
+            }
+            Self::MissingPromotion => write!(f, "a pawn reaching the last rank must promote"),
+            Self::InvalidPromotion(piece) => write!(f, "cannot promote to a {piece}"),
+        }
+    }
+}
+
+impl std::error::Error for IllegalMoveError {}
+
+impl GameState {
+    /// Plays the legal move from `start` to `end`, or explains why there is none.
+    ///
+    /// `promotion` must be given exactly when a pawn reaches the last rank. The move that was
+    /// played is returned, with its [`SpecialMove`] filled in.
+    pub fn try_make_move(
+        &mut self,
+        start: BoardCoordinates,
+        end: BoardCoordinates,
+        promotion: Option<Piece>,
+    ) -> Result<Move, IllegalMoveError> {
+        if let Some(error) = self.explain_illegal_move(start, end) {
+            return Err(error);
+        }
+
+        let mut candidates = self
+            .valid_moves
+            .iter()
+            .filter(|valid_move| valid_move.start == start && valid_move.end == end);
+        let first = *candidates.next().expect("a legal move was found");
+
+        let to_move = match (first.special_move, promotion) {
+            (Some(SpecialMove::PawnPromotion(_)), None) => {
+                return Err(IllegalMoveError::MissingPromotion)
+            }
+            (Some(SpecialMove::PawnPromotion(_)), Some(piece)) => {
+                let promoted = Some(SpecialMove::PawnPromotion(Square::Occupied(
+                    self.position.turn,
+                    piece,
+                )));
+                std::iter::once(&first)
+                    .chain(candidates)
+                    .find(|candidate| candidate.special_move == promoted)
+                    .copied()
+                    .ok_or(IllegalMoveError::InvalidPromotion(piece))?
+            }
+            (_, Some(piece)) => return Err(IllegalMoveError::InvalidPromotion(piece)),
+            (_, None) => first,
+        };
+
+        self.make_new_move(to_move);
+        Ok(to_move)
+    }
+
+    /// Explains why the player to move cannot move from `start` to `end`, or returns `None`
+    /// when they can.
+    ///
+    /// The promotion piece is not looked at, see [`GameState::try_make_move`] for that.
+    pub fn explain_illegal_move(
+        &self,
+        start: BoardCoordinates,
+        end: BoardCoordinates,
+    ) -> Option<IllegalMoveError> {
+        if let Some(game_result) = self.game_result {
+            return Some(IllegalMoveError::GameOver(game_result));
+        }
+        let piece = match self.get_square(start) {
+            Square::Empty => return Some(IllegalMoveError::NoPiece(start)),
+            Square::Occupied(player, _) if player != self.position.turn => {
+                return Some(IllegalMoveError::WrongSide(start))
+            }
+            Square::Occupied(_, piece) => piece,
+        };
+        if self
+            .valid_moves
+            .iter()
+            .any(|valid_move| valid_move.start == start && valid_move.end == end)
+        {
+            return None;
+        }
+
+        let start_index = bitboard::square_index(start);
+        let end_index = bitboard::square_index(end);
+        let (king_home, ..) = self.position.castling_sides(self.position.turn);
+        if piece == Piece::King && start_index == king_home && start_index.abs_diff(end_index) == 2
+        {
+            return Some(self.explain_castling(start_index, end_index));
+        }
+        if let Some(error) = self.explain_movement(piece, start_index, end_index) {
+            return Some(error);
+        }
+
+        // The move follows the rules for the piece, so the king is what it endangers
+        if piece != Piece::King {
+            let king = bitboard::square_index(match self.position.turn {
+                Player::White => self.position.white_king_location,
+                Player::Black => self.position.black_king_location,
+            });
+            if let Some(pinner) = self.position.pinner(start_index) {
+                let pin_ray = bitboard::between(king, pinner) | 1 << pinner;
+                if pin_ray & 1 << end_index == 0 {
+                    return Some(IllegalMoveError::Pinned {
+                        pinner: bitboard::coordinates(pinner),
+                    });
+                }
+            }
+        }
+        Some(IllegalMoveError::LeavesKingInCheck)
+    }
+
+    /// Why the piece cannot get from `start` to `end` however safe its king is, or `None` if it
+    /// could.
+    fn explain_movement(&self, piece: Piece, start: usize, end: usize) -> Option<IllegalMoveError> {
+        let occupied = self.position.occupied();
+        let enemy = self.position.occupancy[opponent(self.position.turn) as usize];
+
+        // Where the piece could go on an empty board, except that pawns only move diagonally
+        // to capture
+        let pattern: Bitboard = match piece {
+            Piece::Pawn => {
+                let captures = self
+                    .position
+                    .en_passant_square
+                    .map_or(enemy, |en_passant_square| {
+                        enemy | bitboard::bit(en_passant_square)
+                    });
+                let (forward, start_row): (isize, usize) = match self.position.turn {
+                    Player::White => (-8, 6),
+                    Player::Black => (8, 1),
+                };
+                let mut pushes = 1 << (start as isize + forward);
+                if start / 8 == start_row {
+                    pushes |= 1 << (start as isize + 2 * forward);
+                }
+                (bitboard::pawn_attacks(self.position.turn, start) & captures) | pushes
+            }
+            Piece::Knight => bitboard::knight_attacks(start),
+            Piece::Bishop => bitboard::bishop_attacks(start, 0),
+            Piece::Rook => bitboard::rook_attacks(start, 0),
+            Piece::Queen => bitboard::queen_attacks(start, 0),
+            Piece::King => bitboard::king_attacks(start),
+        };
+        if pattern & 1 << end == 0 {
+            return Some(IllegalMoveError::InvalidMovement {
+                start: bitboard::coordinates(start),
+                end: bitboard::coordinates(end),
+            });
+        }
+
+        let blockers = bitboard::between(start, end) & occupied;
+        if blockers != 0 {
+            let nearest = if end > start {
+                blockers.trailing_zeros() as usize
+            } else {
+                63 - blockers.leading_zeros() as usize
+            };
+            return Some(IllegalMoveError::PathBlocked(bitboard::coordinates(
+                nearest,
+            )));
+        }
+
+        // Pawns cannot capture straight ahead, and no piece can capture its own side
+        let is_push = piece == Piece::Pawn && start % 8 == end % 8;
+        let own = self.position.occupancy[self.position.turn as usize];
+        if own & 1 << end != 0 || (is_push && occupied & 1 << end != 0) {
+            return Some(IllegalMoveError::PathBlocked(bitboard::coordinates(end)));
+        }
+        None
+    }
+
+    fn explain_castling(&self, king: usize, end: usize) -> IllegalMoveError {
+        let king_side = end > king;
+        let has_right = |castling_rights: &CastlingRights| match (self.position.turn, king_side) {
+            (Player::White, true) => castling_rights.get_white_king_side(),
+            (Player::White, false) => castling_rights.get_white_queen_side(),
+            (Player::Black, true) => castling_rights.get_black_king_side(),
+            (Player::Black, false) => castling_rights.get_black_queen_side(),
+        };
+
+        // Every undo record holds the rights from before its move
+        if let Some(lost_at) = self
+            .undo_stack
+            .iter()
+            .map(|record| &record.position.castling_rights)
+            .chain([&self.position.castling_rights])
+            .position(|rights| !has_right(rights))
+        {
+            return IllegalMoveError::CastlingRightsLost {
+                lost_by: lost_at.checked_sub(1).map(|ply| self.move_log[ply]),
+            };
+        }
+
+        let rook = if king_side { king + 3 } else { king - 4 };
+        let blockers = bitboard::between(king, rook) & self.position.occupied();
+        if blockers != 0 {
+            let nearest = if king_side {
+                blockers.trailing_zeros() as usize
+            } else {
+                63 - blockers.leading_zeros() as usize
+            };
+            return IllegalMoveError::PathBlocked(bitboard::coordinates(nearest));
+        }
+
+        let passed = if king_side {
+            [king, king + 1, king + 2]
+        } else {
+            [king, king - 1, king - 2]
+        };
+        passed
+            .into_iter()
+            .find(|square| {
+                self.position
+                    .is_attacked(*square, opponent(self.position.turn))
+            })
+            .map_or(IllegalMoveError::LeavesKingInCheck, |square| {
+                IllegalMoveError::CastlingThroughCheck(bitboard::coordinates(square))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IllegalMoveError;
+    use crate::prelude::*;
+
+    fn square(name: &str) -> BoardCoordinates {
+        name.parse().unwrap()
+    }
+
+    fn try_move(
+        fen: &str,
+        start: &str,
+        end: &str,
+        promotion: Option<Piece>,
+    ) -> Result<Move, IllegalMoveError> {
+        let mut game_state = GameState::from_fen(fen).unwrap();
+        game_state.try_make_move(square(start), square(end), promotion)
+    }
+
+    #[test]
+    fn plays_legal_moves() {
+        let mut game_state = GameState::new();
+        let played = game_state
+            .try_make_move(square("e2"), square("e4"), None)
+            .unwrap();
+        assert_eq!(played.to_uci(), "e2e4");
+        assert_eq!(game_state.get_turn(), Player::Black);
+
+        let castle = try_move("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1", None).unwrap();
+        assert_eq!(castle.special_move, Some(SpecialMove::Castle));
+
+        let promotion = try_move(
+            "4k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            "a7",
+            "a8",
+            Some(Piece::Knight),
+        );
+        assert_eq!(promotion.unwrap().to_uci(), "a7a8n");
+    }
+
+    #[test]
+    fn reasons() {
+        const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(
+            try_move(START, "e4", "e5", None),
+            Err(IllegalMoveError::NoPiece(square("e4")))
+        );
+        assert_eq!(
+            try_move(START, "e7", "e5", None),
+            Err(IllegalMoveError::WrongSide(square("e7")))
+        );
+        assert_eq!(
+            try_move(START, "f1", "f3", None),
+            Err(IllegalMoveError::InvalidMovement {
+                start: square("f1"),
+                end: square("f3"),
+            })
+        );
+        assert_eq!(
+            try_move(START, "e2", "e4", Some(Piece::Queen)),
+            Err(IllegalMoveError::InvalidPromotion(Piece::Queen))
+        );
+
+        // The king stepping into the rook's line
+        assert_eq!(
+            try_move("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1", "e1", "e2", None),
+            Err(IllegalMoveError::LeavesKingInCheck)
+        );
+
+        const PROMOTION: &str = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(
+            try_move(PROMOTION, "a7", "a8", None),
+            Err(IllegalMoveError::MissingPromotion)
+        );
+        assert_eq!(
+            try_move(PROMOTION, "a7", "a8", Some(Piece::King)),
+            Err(IllegalMoveError::InvalidPromotion(Piece::King))
+        );
+        assert_eq!(
+            IllegalMoveError::InvalidPromotion(Piece::King).to_string(),
+            "cannot promote to a king"
+        );
+    }
+
+    #[test]
+    fn refuses_moves_after_the_game_ended() {
+        let mut game_state = GameState::from_fen("7k/5Q2/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+        game_state
+            .try_make_move(square("f7"), square("g7"), None)
+            .unwrap();
+        assert_eq!(
+            game_state.try_make_move(square("h8"), square("h7"), None),
+            Err(IllegalMoveError::GameOver(GameResult::win(
+                Player::White,
+                Termination::Checkmate
+            )))
+        );
+    }
+
+    fn explain(fen: &str, start: &str, end: &str) -> Option<IllegalMoveError> {
+        GameState::from_fen(fen)
+            .unwrap()
+            .explain_illegal_move(square(start), square(end))
+    }
+
+    #[test]
+    fn explains_movement() {
+        const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(explain(START, "e2", "e4"), None);
+        assert_eq!(
+            explain(START, "f1", "c4"),
+            Some(IllegalMoveError::PathBlocked(square("e2")))
+        );
+        assert_eq!(
+            explain(START, "a1", "a3"),
+            Some(IllegalMoveError::PathBlocked(square("a2")))
+        );
+        assert_eq!(
+            explain(START, "d1", "d2"),
+            Some(IllegalMoveError::PathBlocked(square("d2")))
+        );
+        assert_eq!(
+            explain(START, "e2", "d3"),
+            Some(IllegalMoveError::InvalidMovement {
+                start: square("e2"),
+                end: square("d3"),
+            })
+        );
+        assert_eq!(
+            explain(START, "e2", "e5"),
+            Some(IllegalMoveError::InvalidMovement {
+                start: square("e2"),
+                end: square("e5"),
+            })
+        );
+
+        // Pawns cannot capture straight ahead
+        assert_eq!(
+            explain("4k3/8/8/8/8/4p3/4P3/4K3 w - - 0 1", "e2", "e3"),
+            Some(IllegalMoveError::PathBlocked(square("e3")))
+        );
+    }
+
+    #[test]
+    fn explains_king_safety() {
+        const PINNED: &str = "4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1";
+        assert_eq!(
+            explain(PINNED, "e2", "d3"),
+            Some(IllegalMoveError::Pinned {
+                pinner: square("e7"),
+            })
+        );
+
+        // A pinned rook may move along the pin, but not while another piece gives check
+        const PINNED_IN_CHECK: &str = "4k3/4r3/8/8/1b6/8/4R3/4K3 w - - 0 1";
+        assert_eq!(
+            explain(PINNED_IN_CHECK, "e2", "e5"),
+            Some(IllegalMoveError::LeavesKingInCheck)
+        );
+        assert_eq!(
+            explain(PINNED_IN_CHECK, "e2", "d2"),
+            Some(IllegalMoveError::Pinned {
+                pinner: square("e7"),
+            })
+        );
+        assert_eq!(
+            explain(PINNED_IN_CHECK, "e1", "d2"),
+            Some(IllegalMoveError::LeavesKingInCheck)
+        );
+    }
+
+    #[test]
+    fn explains_castling() {
+        assert_eq!(
+            explain("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1", "e1", "c1"),
+            Some(IllegalMoveError::PathBlocked(square("b1")))
+        );
+        assert_eq!(
+            explain("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1", "e1", "g1"),
+            Some(IllegalMoveError::PathBlocked(square("g1")))
+        );
+        assert_eq!(
+            explain("4k3/8/8/8/8/8/8/R3K2R w Q - 0 1", "e1", "g1"),
+            Some(IllegalMoveError::CastlingRightsLost { lost_by: None })
+        );
+        assert_eq!(
+            explain("4k3/8/8/8/8/5r2/8/R3K2R w KQ - 0 1", "e1", "g1"),
+            Some(IllegalMoveError::CastlingThroughCheck(square("f1")))
+        );
+        assert_eq!(
+            explain("4k3/8/8/8/8/4r3/8/R3K2R w KQ - 0 1", "e1", "c1"),
+            Some(IllegalMoveError::CastlingThroughCheck(square("e1")))
+        );
+
+        let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let rook_move = game_state
+            .try_make_move(square("h1"), square("h2"), None)
+            .unwrap();
+        game_state
+            .try_make_move(square("e8"), square("e7"), None)
+            .unwrap();
+        game_state
+            .try_make_move(square("h2"), square("h1"), None)
+            .unwrap();
+        game_state
+            .try_make_move(square("e7"), square("e8"), None)
+            .unwrap();
+        assert_eq!(
+            game_state.explain_illegal_move(square("e1"), square("g1")),
+            Some(IllegalMoveError::CastlingRightsLost {
+                lost_by: Some(rook_move),
+            })
+        );
+        assert_eq!(
+            game_state.explain_illegal_move(square("e1"), square("c1")),
+            None
+        );
+    }
+}
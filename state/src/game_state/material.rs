@@ -0,0 +1,151 @@
+use crate::bitboard::LIGHT_SQUARES;
+use crate::prelude::*;
+
+/// The pieces one player has besides the king, as far as mating is concerned.
+struct Material {
+    /// Any pawn, rook or queen is enough to mate.
+    has_pawn_or_major_piece: bool,
+    knights: usize,
+    light_square_bishops: usize,
+    dark_square_bishops: usize,
+}
+
+impl Material {
+    fn is_bare_king(&self) -> bool {
+        !self.has_pawn_or_major_piece
+            && self.knights == 0
+            && self.light_square_bishops == 0
+            && self.dark_square_bishops == 0
+    }
+}
+
+impl Position {
+    fn material(&self, player: Player) -> Material {
+        let pieces = self.pieces[player as usize];
+        let bishops = pieces[Piece::Bishop as usize];
+        Material {
+            has_pawn_or_major_piece: pieces[Piece::Pawn as usize]
+                | pieces[Piece::Rook as usize]
+                | pieces[Piece::Queen as usize]
+                != 0,
+            knights: pieces[Piece::Knight as usize].count_ones() as usize,
+            light_square_bishops: (bishops & LIGHT_SQUARES).count_ones() as usize,
+            dark_square_bishops: (bishops & !LIGHT_SQUARES).count_ones() as usize,
+        }
+    }
+
+    /// Whether `player` could still checkmate with some series of legal moves, however
+    /// unlikely, if the opponent cooperated.
+    ///
+    /// A lone knight needs an enemy piece to block the king's escape, and bishops that all
+    /// stand on squares of one colour need an enemy piece that can stand on the other colour.
+    pub fn has_mating_material(&self, player: Player) -> bool {
+        let own = self.material(player);
+        let opponent = self.material(match player {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        });
+
+        let bishops = own.light_square_bishops + own.dark_square_bishops;
+        if own.has_pawn_or_major_piece || own.knights >= 2 || (own.knights == 1 && bishops > 0) {
+            true
+        } else if own.knights == 1 {
+            !opponent.is_bare_king()
+        } else if own.light_square_bishops > 0 && own.dark_square_bishops > 0 {
+            true
+        } else if own.light_square_bishops > 0 {
+            opponent.has_pawn_or_major_piece
+                || opponent.knights > 0
+                || opponent.dark_square_bishops > 0
+        } else if own.dark_square_bishops > 0 {
+            opponent.has_pawn_or_major_piece
+                || opponent.knights > 0
+                || opponent.light_square_bishops > 0
+        } else {
+            false
+        }
+    }
+
+    /// Neither player can checkmate, so the game is drawn however it continues.
+    pub(super) fn is_dead_position(&self) -> bool {
+        !self.has_mating_material(Player::White) && !self.has_mating_material(Player::Black)
+    }
+}
+
+impl GameState {
+    /// Whether `player` could still checkmate, see [`Position::has_mating_material`].
+    pub fn has_mating_material(&self, player: Player) -> bool {
+        self.position.has_mating_material(player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn mating_material(fen: &str) -> (bool, bool) {
+        let game_state = GameState::from_fen(fen).unwrap();
+        (
+            game_state.has_mating_material(Player::White),
+            game_state.has_mating_material(Player::Black),
+        )
+    }
+
+    #[test]
+    fn dead_positions() {
+        // Bare kings, a lone minor piece, and bishops all on squares of one colour
+        for fen in [
+            "8/8/4k3/8/2K5/8/8/8 w - - 0 1",
+            "8/8/4k3/8/2K5/8/5B2/8 w - - 0 1",
+            "8/8/4k3/8/2K5/8/5N2/8 w - - 0 1",
+            "8/2b5/4k3/8/2K5/8/5B2/8 w - - 0 1",
+            "8/2b5/4k3/8/2K5/4B3/5B2/8 w - - 0 1",
+        ] {
+            assert_eq!(mating_material(fen), (false, false), "{fen}");
+        }
+    }
+
+    #[test]
+    fn positions_with_mating_material() {
+        assert_eq!(
+            mating_material("8/8/4k3/8/2K5/8/5P2/8 w - - 0 1"),
+            (true, false)
+        );
+        assert_eq!(
+            mating_material("8/8/4k3/8/2K5/8/4NN2/8 w - - 0 1"),
+            (true, false)
+        );
+        assert_eq!(
+            mating_material("8/8/4k3/8/2K5/8/4BN2/8 w - - 0 1"),
+            (true, false)
+        );
+        assert_eq!(
+            mating_material("8/8/4k3/8/2K5/8/4BB2/8 w - - 0 1"),
+            (true, false)
+        );
+        // A blocker lets the minor piece mate, so neither side is out of material
+        assert_eq!(
+            mating_material("8/8/4k3/8/2K5/8/5N2/q7 w - - 0 1"),
+            (true, true)
+        );
+        assert_eq!(
+            mating_material("8/8/4k3/8/2K5/8/5B2/n7 w - - 0 1"),
+            (true, true)
+        );
+        assert_eq!(
+            mating_material("8/3b4/4k3/8/2K5/8/5B2/8 w - - 0 1"),
+            (true, true)
+        );
+    }
+
+    #[test]
+    fn captures_into_a_dead_position_end_the_game() {
+        let mut game_state = GameState::from_fen("8/8/4k3/8/2K5/8/5B2/6n1 w - - 0 1").unwrap();
+        let capture = game_state.parse_uci_move("f2g1").unwrap();
+        game_state.make_new_move(capture);
+        assert_eq!(
+            game_state.get_game_result(),
+            Some(GameResult::draw(Termination::DeadPosition))
+        );
+    }
+}
@@ -0,0 +1,388 @@
+use crate::prelude::*;
+
+use move_generation::opponent;
+
+mod attacks;
+mod builder;
+mod exchange;
+mod fen;
+mod legality;
+mod material;
+mod move_generation;
+mod outcome;
+mod perft;
+mod position;
+mod san;
+mod uci;
+mod zobrist;
+
+pub use builder::{PositionBuilder, PositionError};
+pub use fen::FenError;
+pub use legality::IllegalMoveError;
+pub use outcome::OutcomeError;
+pub use position::Position;
+pub use san::SanError;
+pub use uci::UciMoveError;
+
+/// Everything a move changes that cannot be worked out from the move itself, saved before the
+/// move is made so that taking it back restores the game exactly.
+#[derive(Clone, Debug, PartialEq)]
+struct UndoRecord {
+    position: Position,
+    game_result: Option<GameResult>,
+    draw_offer: Option<Player>,
+    valid_moves: Vec<Move>,
+}
+
+/// A game in progress: the current [`Position`] together with the moves that led to it and
+/// whatever the rules need to know about them, such as repetitions and draw offers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameState {
+    position: Position,
+
+    move_log: Vec<Move>,
+    valid_moves: Vec<Move>,
+
+    /// One record per move in the move log, for taking the moves back.
+    undo_stack: Vec<UndoRecord>,
+
+    game_result: Option<GameResult>,
+    /// The player whose draw offer is waiting for an answer.
+    draw_offer: Option<Player>,
+    /// Repetition key of every position reached so far, including the current one.
+    position_history: Vec<u64>,
+
+    initial_fen: String,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        Self::from_position(Position::new())
+    }
+
+    /// Starts a game from `position`, as if it had been set up on the board.
+    pub fn from_position(position: Position) -> Self {
+        let mut new_state = Self {
+            position,
+
+            move_log: Vec::new(),
+            valid_moves: Vec::new(),
+
+            undo_stack: Vec::new(),
+
+            game_result: None,
+            draw_offer: None,
+            position_history: Vec::new(),
+
+            initial_fen: position.to_fen(),
+        };
+
+        new_state.generate_valid_moves();
+        new_state.position_history.push(new_state.repetition_key());
+
+        new_state
+    }
+
+    pub fn restart(&mut self) {
+        *self = Self::new();
+    }
+
+    /// The current position, which can be copied and analysed independently of the game.
+    pub fn get_position(&self) -> &Position {
+        &self.position
+    }
+
+    pub fn get_square(&self, coordinates: BoardCoordinates) -> Square {
+        self.position.get_square(coordinates)
+    }
+
+    pub fn best_move(&self) -> Option<Move> {
+        self.search(&SearchLimits::default()).best_move
+    }
+
+    pub fn get_turn(&self) -> Player {
+        self.position.get_turn()
+    }
+
+    pub fn get_white_king_location(&self) -> BoardCoordinates {
+        self.position.get_white_king_location()
+    }
+
+    pub fn get_black_king_location(&self) -> BoardCoordinates {
+        self.position.get_black_king_location()
+    }
+
+    pub fn get_is_check(&self) -> bool {
+        self.position.is_check()
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_result.is_some()
+    }
+
+    pub fn get_game_result(&self) -> Option<GameResult> {
+        self.game_result
+    }
+    pub fn get_en_passant_square(&self) -> Option<BoardCoordinates> {
+        self.position.get_en_passant_square()
+    }
+
+    pub fn get_last_move(&self) -> Option<&Move> {
+        self.move_log.last()
+    }
+
+    pub fn get_valid_moves(&self) -> &Vec<Move> {
+        &self.valid_moves
+    }
+
+    pub fn get_move_counter(&self) -> &MoveCounter {
+        self.position.get_move_counter()
+    }
+
+    pub fn get_move_log(&self) -> &Vec<Move> {
+        &self.move_log
+    }
+
+    /// FEN of the position this game started from, before any move in the move log was played.
+    pub fn get_initial_fen(&self) -> &str {
+        &self.initial_fen
+    }
+
+    /// Describes the current position in Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        self.position.to_fen()
+    }
+
+    /// Plays `new_move`, which must be one of the valid moves. Once the game is over, moves are
+    /// ignored, see [`GameState::try_make_move`] for a version that reports it.
+    pub fn make_new_move(&mut self, new_move: Move) {
+        if self.game_result.is_some() {
+            return;
+        }
+        self.make_move(new_move);
+        self.generate_valid_moves();
+
+        // Moving instead of answering a draw offer declines it
+        if self.draw_offer == Some(self.position.turn) {
+            self.draw_offer = None;
+        }
+
+        // Dead position
+        if self.position.is_dead_position() && self.game_result.is_none() {
+            self.game_result = Some(GameResult::draw(Termination::DeadPosition));
+        }
+
+        // Seventy-five-move rule, unless the last move mated
+        if self.position.move_counter.get_fifty_move_rule_count() >= 150
+            && self.game_result.is_none()
+        {
+            self.game_result = Some(GameResult::draw(Termination::SeventyFiveMoveRule));
+        }
+
+        // Fivefold repetition
+        self.position_history.push(self.repetition_key());
+        self.check_repetition();
+    }
+
+    fn make_move(&mut self, to_move: Move) {
+        self.undo_stack.push(UndoRecord {
+            position: self.position,
+            game_result: self.game_result,
+            draw_offer: self.draw_offer,
+            valid_moves: std::mem::take(&mut self.valid_moves),
+        });
+        self.move_log.push(to_move);
+        self.position.make_move(to_move);
+    }
+
+    pub fn undo_last_move(&mut self) {
+        if self.move_log.is_empty() {
+            return;
+        }
+        self.position_history.pop();
+        self.undo_move();
+    }
+
+    /// Takes back the last move of the move log, restoring the game from before it exactly.
+    fn undo_move(&mut self) {
+        let (Some(_), Some(record)) = (self.move_log.pop(), self.undo_stack.pop()) else {
+            return;
+        };
+        self.position = record.position;
+        self.game_result = record.game_result;
+        self.draw_offer = record.draw_offer;
+        self.valid_moves = record.valid_moves;
+    }
+
+    /// Generates every legal move for the player to move, and ends the game when there is none.
+    fn generate_valid_moves(&mut self) {
+        self.valid_moves = self.position.legal_moves();
+        if self.valid_moves.is_empty() {
+            // The player who cannot move has been mated by the other one
+            self.game_result = Some(if self.position.is_check() {
+                GameResult::win(opponent(self.position.turn), Termination::Checkmate)
+            } else {
+                GameResult::draw(Termination::Stalemate)
+            });
+        }
+    }
+
+    /// How many times the current position has occurred, counting this occurrence.
+    ///
+    /// Positions are the same when the same player is to move, the pieces stand on the same
+    /// squares and the same castling and en passant captures are possible. Only positions
+    /// since the last capture or pawn move are compared, since none before can come back.
+    pub fn repetition_count(&self) -> usize {
+        let reversible_plies = self.position.move_counter.get_fifty_move_rule_count() as usize;
+        let current = self.repetition_key();
+        self.position_history
+            .iter()
+            .rev()
+            .take(reversible_plies + 1)
+            .filter(|key| **key == current)
+            .count()
+    }
+
+    fn check_repetition(&mut self) {
+        if self.game_result.is_none() && self.repetition_count() >= 5 {
+            self.game_result = Some(GameResult::draw(Termination::FivefoldRepetition));
+        }
+    }
+
+    /// Key telling positions apart for the repetition rule. Unlike [`Position::hash`], an en
+    /// passant square only counts when the capture is legal.
+    fn repetition_key(&self) -> u64 {
+        let en_passant_possible = self
+            .valid_moves
+            .iter()
+            .any(|valid_move| valid_move.special_move == Some(SpecialMove::EnPassant));
+        if en_passant_possible {
+            self.position.hash
+        } else {
+            self.position.hash ^ self.position.en_passant_key()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameState;
+    use crate::prelude::*;
+
+    fn play(game_state: &mut GameState, moves: &[&str]) {
+        for uci in moves {
+            let to_move = game_state.parse_uci_move(uci).unwrap();
+            game_state.make_new_move(to_move);
+        }
+    }
+
+    #[test]
+    fn undo_restores_the_game_exactly() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            // Castling both ways, with rooks that can be captured
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            // An en passant square from the FEN string, with promotions close by
+            "rnbqkbnr/pP1p1ppp/8/2pPp3/8/8/P1P1PPPP/RNBQKBNR w KQkq e6 0 5",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        // Random playouts from a fixed seed, so that any failure can be replayed
+        let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut random = move |bound: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % bound as u64) as usize
+        };
+
+        for fen in fens {
+            for _ in 0..8 {
+                let mut game_state = GameState::from_fen(fen).unwrap();
+                for _ in 0..60 {
+                    let moves = game_state.get_valid_moves().clone();
+                    if moves.is_empty() || game_state.is_game_over() {
+                        break;
+                    }
+                    for to_move in &moves {
+                        let mut after_move = game_state.clone();
+                        after_move.make_new_move(*to_move);
+                        after_move.undo_last_move();
+                        assert!(
+                            after_move == game_state,
+                            "undoing {} in {} did not restore the game",
+                            to_move.to_uci(),
+                            game_state.to_fen()
+                        );
+                    }
+                    game_state.make_new_move(moves[random(moves.len())]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn threefold_repetition_counts_the_starting_position() {
+        let mut game_state = GameState::new();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        play(&mut game_state, &shuffle);
+        assert_eq!(game_state.repetition_count(), 2);
+        assert_eq!(game_state.claimable_draw(), None);
+
+        play(&mut game_state, &shuffle);
+        assert_eq!(game_state.repetition_count(), 3);
+        assert_eq!(
+            game_state.claimable_draw(),
+            Some(Termination::ThreefoldRepetition)
+        );
+
+        game_state.undo_last_move();
+        assert_eq!(game_state.claimable_draw(), None);
+    }
+
+    #[test]
+    fn repetition_needs_the_same_castling_rights() {
+        let mut game_state = GameState::new();
+        play(&mut game_state, &["g1f3", "g8f6"]);
+        let rook_shuffle = ["h1g1", "h8g8", "g1h1", "g8h8"];
+        play(&mut game_state, &rook_shuffle);
+        play(&mut game_state, &rook_shuffle);
+        // The board has been seen three times, but twice without castling rights
+        assert_eq!(game_state.repetition_count(), 2);
+        assert_eq!(game_state.claimable_draw(), None);
+
+        play(&mut game_state, &rook_shuffle);
+        assert_eq!(
+            game_state.claimable_draw(),
+            Some(Termination::ThreefoldRepetition)
+        );
+    }
+
+    #[test]
+    fn repetition_needs_the_same_en_passant_captures() {
+        let mut game_state = GameState::from_fen("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1").unwrap();
+        play(&mut game_state, &["e2e4"]);
+        let king_shuffle = ["e8e7", "e1e2", "e7e8", "e2e1"];
+        play(&mut game_state, &king_shuffle);
+        play(&mut game_state, &king_shuffle);
+        // The first occurrence allowed dxe3
+        assert_eq!(game_state.repetition_count(), 2);
+        assert_eq!(game_state.claimable_draw(), None);
+
+        // An en passant capture that would expose the king does not make a difference
+        let mut game_state = GameState::from_fen("8/8/8/8/R2p3k/8/4P3/4K3 w - - 0 1").unwrap();
+        play(&mut game_state, &["e2e4"]);
+        let king_shuffle = ["h4h5", "e1e2", "h5h4", "e2e1"];
+        play(&mut game_state, &king_shuffle);
+        play(&mut game_state, &king_shuffle);
+        assert_eq!(
+            game_state.claimable_draw(),
+            Some(Termination::ThreefoldRepetition)
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use crate::bitboard::{self, Bitboard};
+use crate::prelude::*;
+
+const PROMOTIONS: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+impl Position {
+    /// Whether the king of the player to move is attacked.
+    pub fn is_check(&self) -> bool {
+        let king = bitboard::square_index(self.king_location(self.turn));
+        self.attackers(king, self.occupied()) & self.occupancy[opponent(self.turn) as usize] != 0
+    }
+
+    pub(super) fn king_location(&self, player: Player) -> BoardCoordinates {
+        match player {
+            Player::White => self.white_king_location,
+            Player::Black => self.black_king_location,
+        }
+    }
+
+    pub(crate) fn occupied(&self) -> Bitboard {
+        self.occupancy[0] | self.occupancy[1]
+    }
+
+    pub(crate) fn occupied_by(&self, player: Player) -> Bitboard {
+        self.occupancy[player as usize]
+    }
+
+    pub(crate) fn pieces_of(&self, player: Player, piece: Piece) -> Bitboard {
+        self.pieces[player as usize][piece as usize]
+    }
+
+    /// Pieces of either player that attack `square`, with sliding attacks blocked by
+    /// `occupancy`.
+    pub(super) fn attackers(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        let [white, black] = self.pieces;
+        let bishops = white[Piece::Bishop as usize]
+            | white[Piece::Queen as usize]
+            | black[Piece::Bishop as usize]
+            | black[Piece::Queen as usize];
+        let rooks = white[Piece::Rook as usize]
+            | white[Piece::Queen as usize]
+            | black[Piece::Rook as usize]
+            | black[Piece::Queen as usize];
+
+        // A black pawn attacks `square` from where a white pawn on `square` would attack
+        (bitboard::pawn_attacks(Player::White, square) & black[Piece::Pawn as usize])
+            | (bitboard::pawn_attacks(Player::Black, square) & white[Piece::Pawn as usize])
+            | (bitboard::knight_attacks(square)
+                & (white[Piece::Knight as usize] | black[Piece::Knight as usize]))
+            | (bitboard::king_attacks(square)
+                & (white[Piece::King as usize] | black[Piece::King as usize]))
+            | (bitboard::bishop_attacks(square, occupancy) & bishops)
+            | (bitboard::rook_attacks(square, occupancy) & rooks)
+    }
+
+    pub(super) fn is_attacked(&self, square: usize, by: Player) -> bool {
+        self.attackers(square, self.occupied()) & self.occupancy[by as usize] != 0
+    }
+
+    /// Sliding pieces of the opponent of `player` lined up with the king of `player` on a line
+    /// they move along, whatever stands between them.
+    pub(super) fn snipers(&self, player: Player) -> Bitboard {
+        let king = bitboard::square_index(self.king_location(player));
+        let enemy = opponent(player);
+        let queens = self.pieces_of(enemy, Piece::Queen);
+        (bitboard::rook_attacks(king, 0) & (self.pieces_of(enemy, Piece::Rook) | queens))
+            | (bitboard::bishop_attacks(king, 0) & (self.pieces_of(enemy, Piece::Bishop) | queens))
+    }
+
+    /// The enemy piece pinning the piece on `square` to the king of the player to move.
+    pub(super) fn pinner(&self, square: usize) -> Option<usize> {
+        let king = bitboard::square_index(self.king_location(self.turn));
+        let occupied = self.occupied();
+        bitboard::squares(self.snipers(self.turn))
+            .find(|sniper| bitboard::between(king, *sniper) & occupied == 1 << square)
+    }
+
+    /// For every square, the squares its piece may move to without exposing its own king to a
+    /// sliding piece. Pieces that are not pinned may move anywhere.
+    fn pin_rays(&self, king: usize) -> [Bitboard; 64] {
+        let mut pin_rays = [Bitboard::MAX; 64];
+        let own = self.occupancy[self.turn as usize];
+        let occupied = self.occupied();
+
+        for sniper in bitboard::squares(self.snipers(self.turn)) {
+            let between = bitboard::between(king, sniper);
+            let blockers = between & occupied;
+            if blockers.count_ones() == 1 && blockers & own != 0 {
+                pin_rays[blockers.trailing_zeros() as usize] = between | 1 << sniper;
+            }
+        }
+        pin_rays
+    }
+
+    /// Every legal move for the player to move.
+    ///
+    /// Checks and pins are worked out once for the position, so no move has to be played to
+    /// find out whether it leaves the king in check.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(64);
+
+        let enemy = self.occupancy[opponent(self.turn) as usize];
+        let own = self.occupancy[self.turn as usize];
+        let occupied = own | enemy;
+        let king = bitboard::square_index(self.king_location(self.turn));
+        let checkers = self.attackers(king, occupied) & enemy;
+
+        // The king must not stay on the line of a slider it is moving away from
+        let without_king = occupied & !(1 << king);
+        for end in bitboard::squares(bitboard::king_attacks(king) & !own) {
+            if self.attackers(end, without_king) & enemy == 0 {
+                self.push_move(king, end, None, &mut moves);
+            }
+        }
+
+        // Only the king can answer a double check
+        if checkers.count_ones() < 2 {
+            let targets = match checkers {
+                0 => !own,
+                _ => checkers | bitboard::between(king, checkers.trailing_zeros() as usize),
+            };
+            let pin_rays = self.pin_rays(king);
+
+            for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+                for start in bitboard::squares(self.pieces_of(self.turn, piece)) {
+                    let attacks = match piece {
+                        Piece::Knight => bitboard::knight_attacks(start),
+                        Piece::Bishop => bitboard::bishop_attacks(start, occupied),
+                        Piece::Rook => bitboard::rook_attacks(start, occupied),
+                        _ => bitboard::queen_attacks(start, occupied),
+                    };
+                    for end in bitboard::squares(attacks & targets & pin_rays[start]) {
+                        self.push_move(start, end, None, &mut moves);
+                    }
+                }
+            }
+
+            self.generate_pawn_moves(targets, &pin_rays, &mut moves);
+            if checkers == 0 {
+                self.generate_castling_moves(&mut moves);
+            }
+        }
+
+        moves
+    }
+
+    fn generate_pawn_moves(
+        &self,
+        targets: Bitboard,
+        pin_rays: &[Bitboard; 64],
+        moves: &mut Vec<Move>,
+    ) {
+        let enemy = self.occupancy[opponent(self.turn) as usize];
+        let promotion_row = match self.turn {
+            Player::White => 0,
+            Player::Black => 7,
+        };
+
+        for start in bitboard::squares(self.pieces_of(self.turn, Piece::Pawn)) {
+            let allowed = targets & pin_rays[start];
+            let ends = (bitboard::pawn_attacks(self.turn, start) & enemy)
+                | self.pawn_pushes(self.turn, start);
+
+            for end in bitboard::squares(ends & allowed) {
+                if end / 8 == promotion_row {
+                    for piece in PROMOTIONS {
+                        let promoted = Square::Occupied(self.turn, piece);
+                        self.push_move(
+                            start,
+                            end,
+                            Some(SpecialMove::PawnPromotion(promoted)),
+                            moves,
+                        );
+                    }
+                } else {
+                    self.push_move(start, end, None, moves);
+                }
+            }
+
+            if let Some(en_passant_square) = self.en_passant_square {
+                let end = bitboard::square_index(en_passant_square);
+                if bitboard::pawn_attacks(self.turn, start) & 1 << end != 0
+                    && self.is_legal_en_passant(start, end)
+                {
+                    self.push_move(start, end, Some(SpecialMove::EnPassant), moves);
+                }
+            }
+        }
+    }
+
+    /// En passant removes two pawns from the same rank at once, which can uncover an attack
+    /// that pins do not account for, so the resulting position is checked directly.
+    fn is_legal_en_passant(&self, start: usize, end: usize) -> bool {
+        let captured = (start / 8) * 8 + end % 8;
+        let occupied = (self.occupied() & !(1 << start) & !(1 << captured)) | 1 << end;
+        let king = bitboard::square_index(self.king_location(self.turn));
+        let enemy = self.occupancy[opponent(self.turn) as usize] & !(1 << captured);
+        self.attackers(king, occupied) & enemy == 0
+    }
+
+    /// Squares a pawn of `player` on `start` can advance to, one or two squares forward.
+    fn pawn_pushes(&self, player: Player, start: usize) -> Bitboard {
+        let (forward, start_row): (isize, usize) = match player {
+            Player::White => (-8, 6),
+            Player::Black => (8, 1),
+        };
+        let occupied = self.occupied();
+
+        let mut pushes = 0;
+        let one_step = (start as isize + forward) as usize;
+        if occupied & 1 << one_step == 0 {
+            pushes |= 1 << one_step;
+            let two_steps = (one_step as isize + forward) as usize;
+            if start / 8 == start_row && occupied & 1 << two_steps == 0 {
+                pushes |= 1 << two_steps;
+            }
+        }
+        pushes
+    }
+
+    /// The home square of the king of `player`, and whether it may still castle king side and
+    /// queen side.
+    pub(super) fn castling_sides(&self, player: Player) -> (usize, bool, bool) {
+        let castling_rights = self.castling_rights;
+        match player {
+            Player::White => (
+                60,
+                castling_rights.get_white_king_side(),
+                castling_rights.get_white_queen_side(),
+            ),
+            Player::Black => (
+                4,
+                castling_rights.get_black_king_side(),
+                castling_rights.get_black_queen_side(),
+            ),
+        }
+    }
+
+    fn generate_castling_moves(&self, moves: &mut Vec<Move>) {
+        let (king, king_side, queen_side) = self.castling_sides(self.turn);
+        let enemy = self.occupancy[opponent(self.turn) as usize];
+        let occupied = self.occupied();
+        let is_safe = |square: usize| self.attackers(square, occupied) & enemy == 0;
+
+        // The squares between king and rook must be empty, and the king must not pass through
+        // or land on an attacked square
+        if king_side
+            && occupied & bitboard::between(king, king + 3) == 0
+            && is_safe(king + 1)
+            && is_safe(king + 2)
+        {
+            self.push_move(king, king + 2, Some(SpecialMove::Castle), moves);
+        }
+        if queen_side
+            && occupied & bitboard::between(king, king - 4) == 0
+            && is_safe(king - 1)
+            && is_safe(king - 2)
+        {
+            self.push_move(king, king - 2, Some(SpecialMove::Castle), moves);
+        }
+    }
+
+    fn push_move(
+        &self,
+        start: usize,
+        end: usize,
+        special_move: Option<SpecialMove>,
+        moves: &mut Vec<Move>,
+    ) {
+        moves.push(Move::new(
+            bitboard::coordinates(start),
+            bitboard::coordinates(end),
+            special_move,
+            self,
+        ));
+    }
+}
+
+pub(super) fn opponent(player: Player) -> Player {
+    match player {
+        Player::White => Player::Black,
+        Player::Black => Player::White,
+    }
+}
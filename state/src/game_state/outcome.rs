@@ -0,0 +1,241 @@
+use std::fmt;
+
+use super::move_generation::opponent;
+use crate::prelude::*;
+
+/// Reason why a player cannot resign, offer, answer or claim a draw.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutcomeError {
+    GameOver(GameResult),
+    /// The opponent of the player has not offered a draw.
+    NoDrawOffer,
+    /// Neither the threefold repetition nor the fifty-move rule applies.
+    NoDrawToClaim,
+}
+
+impl fmt::Display for OutcomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GameOver(result) => write!(f, "the game is over: {result}"),
+            Self::NoDrawOffer => write!(f, "no draw has been offered"),
+            Self::NoDrawToClaim => write!(
+                f,
+                "the position has not occurred three times and fifty moves have not been played \
+                 without a capture or pawn move"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OutcomeError {}
+
+impl GameState {
+    /// Ends the game with a win for the opponent of `player`.
+    pub fn resign(&mut self, player: Player) -> Result<GameResult, OutcomeError> {
+        self.end_game(GameResult::win(opponent(player), Termination::Resignation))
+    }
+
+    /// Ends the game after `player` ran out of time. The opponent wins, unless they could not
+    /// checkmate by any series of legal moves, in which case the game is drawn.
+    pub fn time_out(&mut self, player: Player) -> Result<GameResult, OutcomeError> {
+        let winner = opponent(player);
+        if self.position.has_mating_material(winner) {
+            self.end_game(GameResult::win(winner, Termination::Timeout))
+        } else {
+            self.end_game(GameResult::draw(Termination::Timeout))
+        }
+    }
+
+    /// Offers a draw to the opponent of `player`. The offer stands until the opponent accepts
+    /// or declines it, or makes a move.
+    pub fn offer_draw(&mut self, player: Player) -> Result<(), OutcomeError> {
+        self.ensure_not_over()?;
+        self.draw_offer = Some(player);
+        Ok(())
+    }
+
+    /// The player whose draw offer is waiting for an answer.
+    pub fn get_draw_offer(&self) -> Option<Player> {
+        self.draw_offer
+    }
+
+    /// Ends the game in a draw if the opponent of `player` has offered one.
+    pub fn accept_draw(&mut self, player: Player) -> Result<GameResult, OutcomeError> {
+        self.ensure_not_over()?;
+        if self.draw_offer != Some(opponent(player)) {
+            return Err(OutcomeError::NoDrawOffer);
+        }
+        self.end_game(GameResult::draw(Termination::DrawAgreement))
+    }
+
+    pub fn decline_draw(&mut self, player: Player) -> Result<(), OutcomeError> {
+        self.ensure_not_over()?;
+        if self.draw_offer != Some(opponent(player)) {
+            return Err(OutcomeError::NoDrawOffer);
+        }
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    /// The rule under which the player to move may claim a draw, if any.
+    ///
+    /// Unlike fivefold repetition and the seventy-five-move rule, which end the game on their
+    /// own, these draws only happen when a player asks for them.
+    pub fn claimable_draw(&self) -> Option<Termination> {
+        if self.game_result.is_some() {
+            None
+        } else if self.repetition_count() >= 3 {
+            Some(Termination::ThreefoldRepetition)
+        } else if self.position.move_counter.get_fifty_move_rule_count() >= 100 {
+            Some(Termination::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+
+    /// Ends the game in a draw under the threefold repetition or the fifty-move rule.
+    pub fn claim_draw(&mut self) -> Result<GameResult, OutcomeError> {
+        self.ensure_not_over()?;
+        let termination = self.claimable_draw().ok_or(OutcomeError::NoDrawToClaim)?;
+        self.end_game(GameResult::draw(termination))
+    }
+
+    fn ensure_not_over(&self) -> Result<(), OutcomeError> {
+        match self.game_result {
+            Some(game_result) => Err(OutcomeError::GameOver(game_result)),
+            None => Ok(()),
+        }
+    }
+
+    fn end_game(&mut self, game_result: GameResult) -> Result<GameResult, OutcomeError> {
+        self.ensure_not_over()?;
+        self.game_result = Some(game_result);
+        self.draw_offer = None;
+        Ok(game_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn play(game_state: &mut GameState, moves: &[&str]) {
+        for uci in moves {
+            let to_move = game_state.parse_uci_move(uci).unwrap();
+            game_state.make_new_move(to_move);
+        }
+    }
+
+    #[test]
+    fn resignation() {
+        let mut game_state = GameState::new();
+        let result = game_state.resign(Player::White).unwrap();
+        assert_eq!(result.get_winner(), Some(Player::Black));
+        assert_eq!(result.get_termination(), Termination::Resignation);
+        assert_eq!(game_state.get_game_result(), Some(result));
+        assert_eq!(
+            game_state.resign(Player::Black),
+            Err(OutcomeError::GameOver(result))
+        );
+
+        // Moving on does not take the resignation back
+        let e4 = game_state.parse_uci_move("e2e4").unwrap();
+        game_state.make_new_move(e4);
+        assert_eq!(game_state.get_game_result(), Some(result));
+        assert!(game_state.get_move_log().is_empty());
+    }
+
+    #[test]
+    fn timeout_needs_mating_material() {
+        let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(
+            game_state.clone().time_out(Player::Black),
+            Ok(GameResult::win(Player::White, Termination::Timeout))
+        );
+        assert_eq!(
+            game_state.time_out(Player::White),
+            Ok(GameResult::draw(Termination::Timeout))
+        );
+    }
+
+    #[test]
+    fn draw_offers() {
+        let mut game_state = GameState::new();
+        play(&mut game_state, &["e2e4"]);
+        game_state.offer_draw(Player::White).unwrap();
+        assert_eq!(game_state.get_draw_offer(), Some(Player::White));
+        // A player cannot accept their own offer
+        assert_eq!(
+            game_state.accept_draw(Player::White),
+            Err(OutcomeError::NoDrawOffer)
+        );
+
+        game_state.decline_draw(Player::Black).unwrap();
+        assert_eq!(game_state.get_draw_offer(), None);
+
+        // Replying with a move declines the offer too
+        game_state.offer_draw(Player::White).unwrap();
+        play(&mut game_state, &["e7e5"]);
+        assert_eq!(game_state.get_draw_offer(), None);
+
+        game_state.offer_draw(Player::Black).unwrap();
+        assert_eq!(
+            game_state.accept_draw(Player::White),
+            Ok(GameResult::draw(Termination::DrawAgreement))
+        );
+        assert!(game_state.is_game_over());
+    }
+
+    #[test]
+    fn claims_and_automatic_draws() {
+        let mut game_state = GameState::new();
+        assert_eq!(game_state.claim_draw(), Err(OutcomeError::NoDrawToClaim));
+
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        play(&mut game_state, &shuffle);
+        play(&mut game_state, &shuffle);
+        assert_eq!(
+            game_state.claimable_draw(),
+            Some(Termination::ThreefoldRepetition)
+        );
+        assert_eq!(game_state.get_game_result(), None);
+
+        // Left unclaimed, the fifth occurrence ends the game
+        let mut unclaimed = game_state.clone();
+        play(&mut unclaimed, &shuffle);
+        assert_eq!(unclaimed.get_game_result(), None);
+        play(&mut unclaimed, &shuffle);
+        assert_eq!(
+            unclaimed.get_game_result(),
+            Some(GameResult::draw(Termination::FivefoldRepetition))
+        );
+        // The rules ended the game, so playing on changes nothing
+        let fen = unclaimed.to_fen();
+        play(&mut unclaimed, &["g1f3"]);
+        assert_eq!(unclaimed.to_fen(), fen);
+        assert_eq!(
+            unclaimed.get_game_result(),
+            Some(GameResult::draw(Termination::FivefoldRepetition))
+        );
+
+        assert_eq!(
+            game_state.claim_draw(),
+            Ok(GameResult::draw(Termination::ThreefoldRepetition))
+        );
+
+        let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert_eq!(game_state.claimable_draw(), None);
+        play(&mut game_state, &["a1a2"]);
+        assert_eq!(
+            game_state.claimable_draw(),
+            Some(Termination::FiftyMoveRule)
+        );
+
+        let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 80").unwrap();
+        play(&mut game_state, &["a1a2"]);
+        assert_eq!(
+            game_state.get_game_result(),
+            Some(GameResult::draw(Termination::SeventyFiveMoveRule))
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::prelude::*;
+
+impl GameState {
+    /// Counts the positions reached after every sequence of `depth` legal moves.
+    ///
+    /// Comparing the count with published numbers is the usual way to find move generation
+    /// bugs. Games ending by repetition or the fifty-move rule are not taken into account, and
+    /// the game is left as it was.
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        if depth == 1 {
+            return self.valid_moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for to_move in self.valid_moves.clone() {
+            self.make_move(to_move);
+            self.generate_valid_moves();
+            nodes += self.perft(depth - 1);
+            self.undo_move();
+        }
+
+        nodes
+    }
+
+    /// Splits [`GameState::perft`] by the first move, written in UCI notation and sorted, to
+    /// narrow down which move another engine disagrees on.
+    pub fn perft_divide(&mut self, depth: usize) -> Vec<(String, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        let mut divide: Vec<(String, u64)> = self
+            .valid_moves
+            .clone()
+            .into_iter()
+            .map(|to_move| {
+                self.make_move(to_move);
+                self.generate_valid_moves();
+                let nodes = self.perft(depth - 1);
+                self.undo_move();
+                (to_move.to_uci(), nodes)
+            })
+            .collect();
+
+        divide.sort();
+        divide
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    // Positions and counts from https://www.chessprogramming.org/Perft_Results
+    const STARTING: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const POSITION_4_MIRRORED: &str =
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+    const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    const POSITION_6: &str =
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    fn perft(fen: &str, depth: usize) -> u64 {
+        GameState::from_fen(fen).unwrap().perft(depth)
+    }
+
+    #[test]
+    fn reference_positions() {
+        assert_eq!(perft(STARTING, 0), 1);
+        assert_eq!(perft(STARTING, 1), 20);
+        assert_eq!(perft(STARTING, 4), 197_281);
+        assert_eq!(perft(KIWIPETE, 1), 48);
+        assert_eq!(perft(KIWIPETE, 3), 97_862);
+        assert_eq!(perft(POSITION_3, 4), 43_238);
+        assert_eq!(perft(POSITION_4, 3), 9_467);
+        assert_eq!(perft(POSITION_4_MIRRORED, 3), 9_467);
+        assert_eq!(perft(POSITION_5, 3), 62_379);
+        assert_eq!(perft(POSITION_6, 3), 89_890);
+    }
+
+    #[test]
+    fn en_passant_edge_cases() {
+        // The capture would expose the king along the rank
+        assert_eq!(perft("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1", 1), 6);
+        // The pawn that just moved gives check, and taking it en passant answers it
+        assert_eq!(perft("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", 1), 9);
+        // Set up from FEN, the en passant square must survive searching the other moves
+        assert_eq!(perft("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", 2), 38);
+    }
+
+    #[test]
+    fn divide_by_first_move() {
+        let mut game_state = GameState::new();
+        let divide = game_state.perft_divide(2);
+        assert_eq!(divide.len(), 20);
+        assert_eq!(divide[0], (String::from("a2a3"), 20));
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 400);
+        assert!(game_state.perft_divide(0).is_empty());
+
+        // Counting leaves the game untouched
+        assert_eq!(game_state.get_valid_moves().len(), 20);
+        assert_eq!(game_state.to_fen(), STARTING);
+    }
+
+    #[test]
+    #[ignore = "takes a few seconds in debug builds"]
+    fn deep_reference_positions() {
+        assert_eq!(perft(STARTING, 5), 4_865_609);
+        assert_eq!(perft(KIWIPETE, 4), 4_085_603);
+        assert_eq!(perft(POSITION_3, 5), 674_624);
+        assert_eq!(perft(POSITION_4, 4), 422_333);
+        assert_eq!(perft(POSITION_5, 4), 2_103_487);
+        assert_eq!(perft(POSITION_6, 4), 3_894_594);
+    }
+}
@@ -0,0 +1,281 @@
+use super::zobrist;
+use crate::bitboard::{self, Bitboard};
+use crate::prelude::*;
+
+/// The pieces on the board together with everything else that decides which moves are legal:
+/// the player to move, castling rights, the en passant square and the move counters.
+///
+/// Unlike [`GameState`], a position knows nothing of how it was reached. It is a small value
+/// that can be copied freely, so it can be handed to other threads or kept around for
+/// analysis while the game goes on.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Position {
+    pub(super) board: [[Square; 8]; 8],
+    /// The same pieces as `board`, one set of squares per player and piece.
+    pub(super) pieces: [[Bitboard; 6]; 2],
+    pub(super) occupancy: [Bitboard; 2],
+
+    pub(super) turn: Player,
+
+    pub(super) en_passant_square: Option<BoardCoordinates>,
+    pub(super) castling_rights: CastlingRights,
+
+    pub(super) move_counter: MoveCounter,
+
+    pub(super) white_king_location: BoardCoordinates,
+    pub(super) black_king_location: BoardCoordinates,
+
+    pub(super) hash: u64,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Position {
+    /// The starting position of a game of chess.
+    pub fn new() -> Self {
+        Self::from_parts(
+            INITIAL_POSITION,
+            Player::White,
+            CastlingRights::default(),
+            None,
+            MoveCounter::new(),
+        )
+    }
+
+    pub(super) fn from_parts(
+        board: [[Square; 8]; 8],
+        turn: Player,
+        castling_rights: CastlingRights,
+        en_passant_square: Option<BoardCoordinates>,
+        move_counter: MoveCounter,
+    ) -> Self {
+        let find_king = |player| {
+            (0..8usize)
+                .flat_map(|row| (0..8usize).map(move |col| BoardCoordinates::new(row, col)))
+                .find(|coordinates| {
+                    board[coordinates.row()][coordinates.col()]
+                        == Square::Occupied(player, Piece::King)
+                })
+                .expect("every position must have one king per player")
+        };
+
+        let mut pieces = [[0; 6]; 2];
+        let mut occupancy = [0; 2];
+        for (row, rank) in board.iter().enumerate() {
+            for (col, square) in rank.iter().enumerate() {
+                if let Square::Occupied(player, piece) = square {
+                    let bit = bitboard::bit(BoardCoordinates::new(row, col));
+                    pieces[*player as usize][*piece as usize] |= bit;
+                    occupancy[*player as usize] |= bit;
+                }
+            }
+        }
+
+        let mut position = Self {
+            board,
+            pieces,
+            occupancy,
+
+            turn,
+
+            en_passant_square,
+            castling_rights,
+
+            move_counter,
+
+            white_king_location: find_king(Player::White),
+            black_king_location: find_king(Player::Black),
+
+            hash: 0,
+        };
+        position.hash = position.compute_hash();
+        position
+    }
+
+    pub fn get_square(&self, coordinates: BoardCoordinates) -> Square {
+        self.board[coordinates.row()][coordinates.col()]
+    }
+
+    pub fn get_turn(&self) -> Player {
+        self.turn
+    }
+
+    pub fn get_white_king_location(&self) -> BoardCoordinates {
+        self.white_king_location
+    }
+
+    pub fn get_black_king_location(&self) -> BoardCoordinates {
+        self.black_king_location
+    }
+
+    pub fn get_en_passant_square(&self) -> Option<BoardCoordinates> {
+        self.en_passant_square
+    }
+
+    pub fn get_castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    pub fn get_move_counter(&self) -> &MoveCounter {
+        &self.move_counter
+    }
+
+    /// The position after `to_move`, which must be one of the legal moves of this position.
+    pub fn play(&self, to_move: Move) -> Position {
+        let mut position = *self;
+        position.make_move(to_move);
+        position
+    }
+
+    pub(super) fn make_move(&mut self, to_move: Move) {
+        self.set_en_passant_square(None);
+
+        self.set_square(to_move.start, Square::Empty);
+        self.set_square(to_move.end, to_move.piece_moved);
+
+        if let Some(special_move) = to_move.special_move {
+            if let SpecialMove::PawnPromotion(square) = special_move {
+                self.set_square(to_move.end, square);
+            } else if special_move == SpecialMove::EnPassant {
+                let captured_pawn = BoardCoordinates::new(to_move.start.row(), to_move.end.col());
+                self.set_square(captured_pawn, Square::Empty)
+            } else if special_move == SpecialMove::Castle {
+                let (rook_start, rook_end) =
+                    if (to_move.start.col() as i8 - to_move.end.col() as i8) < 0 {
+                        (
+                            BoardCoordinates::new(to_move.end.row(), to_move.end.col() + 1),
+                            BoardCoordinates::new(to_move.end.row(), to_move.end.col() - 1),
+                        )
+                    } else {
+                        (
+                            BoardCoordinates::new(to_move.end.row(), to_move.end.col() - 2),
+                            BoardCoordinates::new(to_move.end.row(), to_move.end.col() + 1),
+                        )
+                    };
+
+                self.set_square(rook_end, self.get_square(rook_start));
+                self.set_square(rook_start, Square::Empty);
+            }
+        }
+
+        if let Square::Occupied(player, piece) = to_move.piece_moved {
+            if piece == Piece::Pawn {
+                let jump_distance = to_move.start.row().abs_diff(to_move.end.row());
+                if jump_distance == 2 {
+                    self.set_en_passant_square(Some(BoardCoordinates::new(
+                        if player == Player::White {
+                            to_move.start.row() - 1
+                        } else {
+                            to_move.start.row() + 1
+                        },
+                        to_move.start.col(),
+                    )));
+                }
+            }
+        }
+
+        self.update_castle_rights(to_move);
+
+        // Fifty-move rule
+        let irreversible = match to_move.piece_moved {
+            Square::Occupied(_, piece) => piece == Piece::Pawn,
+            Square::Empty => false,
+        } || to_move.piece_captured != Square::Empty;
+        self.move_counter.increment(irreversible);
+
+        self.change_turn();
+    }
+
+    /// Changes one square, keeping the bitboards and the hash in step. King locations, castling
+    /// rights and the en passant square are left for the caller to update.
+    pub(super) fn set_square(&mut self, coordinates: BoardCoordinates, square: Square) {
+        let bit = bitboard::bit(coordinates);
+        for square in [self.get_square(coordinates), square] {
+            if let Square::Occupied(player, piece) = square {
+                self.pieces[player as usize][piece as usize] ^= bit;
+                self.occupancy[player as usize] ^= bit;
+            }
+        }
+        self.hash ^= zobrist::square_key(coordinates, self.get_square(coordinates))
+            ^ zobrist::square_key(coordinates, square);
+        self.board[coordinates.row()][coordinates.col()] = square;
+    }
+
+    fn update_castle_rights(&mut self, to_move: Move) {
+        let mut new_castling_rights = self.castling_rights;
+
+        if to_move.piece_moved == Square::Occupied(Player::White, Piece::King) {
+            self.white_king_location = to_move.end;
+            new_castling_rights.ban_white_king_side();
+            new_castling_rights.ban_white_queen_side();
+        } else if to_move.piece_moved == Square::Occupied(Player::Black, Piece::King) {
+            self.black_king_location = to_move.end;
+            new_castling_rights.ban_black_king_side();
+            new_castling_rights.ban_black_queen_side();
+        }
+
+        let mut check_then_ban = |x, y| {
+            match (x, y) {
+                (0, 0) => new_castling_rights.ban_black_queen_side(),
+                (0, 7) => new_castling_rights.ban_black_king_side(),
+                (7, 0) => new_castling_rights.ban_white_queen_side(),
+                (7, 7) => new_castling_rights.ban_white_king_side(),
+                _ => {}
+            };
+        };
+
+        if let Square::Occupied(_, piece_moved) = to_move.piece_moved {
+            if piece_moved == Piece::Rook {
+                check_then_ban(to_move.start.row(), to_move.start.col());
+            }
+        }
+        if let Square::Occupied(_, piece_captured) = to_move.piece_captured {
+            if piece_captured == Piece::Rook {
+                check_then_ban(to_move.end.row(), to_move.end.col());
+            }
+        }
+        self.hash ^= zobrist::castling_key(self.castling_rights)
+            ^ zobrist::castling_key(new_castling_rights);
+        self.castling_rights = new_castling_rights;
+    }
+
+    fn change_turn(&mut self) {
+        self.hash ^= zobrist::turn_key(Player::White) ^ zobrist::turn_key(Player::Black);
+        self.turn = match self.turn {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn playing_leaves_the_position_alone() {
+        let start = Position::new();
+        let e4 = GameState::new().parse_uci_move("e2e4").unwrap();
+        let after_e4 = start.play(e4);
+
+        assert_eq!(start, *GameState::new().get_position());
+        assert_eq!(after_e4.get_turn(), Player::Black);
+        assert_eq!(
+            after_e4.get_en_passant_square(),
+            Some(BoardCoordinates::new(5, 4))
+        );
+        assert_eq!(after_e4.legal_moves().len(), 20);
+
+        let mut game_state = GameState::new();
+        game_state.make_new_move(e4);
+        assert_eq!(*game_state.get_position(), after_e4);
+
+        // Positions are plain values that threads can share
+        fn assert_send_sync<T: Send + Sync + Copy>() {}
+        assert_send_sync::<Position>();
+    }
+}
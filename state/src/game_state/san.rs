@@ -0,0 +1,335 @@
+use std::fmt;
+
+use crate::prelude::*;
+
+/// Reason why a move written in Standard Algebraic Notation was rejected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SanError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSyntax(san) => write!(f, "`{san}` is not a move in algebraic notation"),
+            Self::IllegalMove(san) => write!(f, "`{san}` is not a legal move"),
+            Self::AmbiguousMove(san) => write!(f, "`{san}` matches more than one legal move"),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+impl GameState {
+    /// Writes a legal move in Standard Algebraic Notation, including the check or checkmate
+    /// suffix.
+    ///
+    /// `to_move` should be one of [`GameState::get_valid_moves`].
+    pub fn move_to_san(&self, to_move: &Move) -> String {
+        let mut san = String::new();
+
+        if to_move.special_move == Some(SpecialMove::Castle) {
+            if to_move.end.col() > to_move.start.col() {
+                san.push_str("O-O");
+            } else {
+                san.push_str("O-O-O");
+            }
+        } else if let Square::Occupied(_, piece) = to_move.piece_moved {
+            let is_capture = to_move.piece_captured != Square::Empty
+                || to_move.special_move == Some(SpecialMove::EnPassant);
+
+            if piece == Piece::Pawn {
+                if is_capture {
+                    san.push(file_name(to_move.start));
+                }
+            } else {
+                san.push(piece_letter(piece));
+                san.push_str(&self.disambiguation(to_move));
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&to_move.end.to_string());
+            if let Some(SpecialMove::PawnPromotion(Square::Occupied(_, promoted))) =
+                to_move.special_move
+            {
+                san.push('=');
+                san.push(piece_letter(promoted));
+            }
+        }
+
+        let after_move = self.position.play(*to_move);
+        if after_move.is_check() {
+            if after_move.legal_moves().is_empty() {
+                san.push('#');
+            } else {
+                san.push('+');
+            }
+        }
+
+        san
+    }
+
+    /// Finds the legal move written in Standard Algebraic Notation.
+    ///
+    /// Check, checkmate and annotation suffixes are ignored, and so are redundant
+    /// disambiguation characters.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let original = san;
+        let invalid_syntax = || SanError::InvalidSyntax(original.to_string());
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+        if matches!(san, "O-O" | "0-0" | "O-O-O" | "0-0-0") {
+            let king_side = san.len() == 3;
+            return self
+                .valid_moves
+                .iter()
+                .copied()
+                .find(|valid_move| {
+                    valid_move.special_move == Some(SpecialMove::Castle)
+                        && (valid_move.end.col() > valid_move.start.col()) == king_side
+                })
+                .ok_or_else(|| SanError::IllegalMove(original.to_string()));
+        }
+
+        let mut chars: Vec<char> = san.chars().collect();
+
+        let piece = match chars.first() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let piece = letter_piece(*c).ok_or_else(invalid_syntax)?;
+                chars.remove(0);
+                piece
+            }
+            _ => Piece::Pawn,
+        };
+
+        let mut promotion = None;
+        if let Some(last) = chars.last() {
+            if last.is_ascii_uppercase() {
+                promotion = Some(letter_piece(*last).ok_or_else(invalid_syntax)?);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        if chars.len() < 2 {
+            return Err(invalid_syntax());
+        }
+        let end: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let end = end
+            .parse::<BoardCoordinates>()
+            .map_err(|_| invalid_syntax())?;
+
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+        let mut from_file = None;
+        let mut from_row = None;
+        for c in chars {
+            match c {
+                'a'..='h' if from_file.is_none() => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' if from_row.is_none() => from_row = Some(8 - (c as usize - '0' as usize)),
+                _ => return Err(invalid_syntax()),
+            }
+        }
+
+        let mut candidates = self.valid_moves.iter().filter(|valid_move| {
+            let promotion_matches = match valid_move.special_move {
+                Some(SpecialMove::PawnPromotion(Square::Occupied(_, promoted))) => {
+                    promotion == Some(promoted)
+                }
+                _ => promotion.is_none(),
+            };
+            valid_move.piece_moved == Square::Occupied(self.position.turn, piece)
+                && valid_move.end == end
+                && valid_move.special_move != Some(SpecialMove::Castle)
+                && from_file.is_none_or(|col| valid_move.start.col() == col)
+                && from_row.is_none_or(|row| valid_move.start.row() == row)
+                && promotion_matches
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(found), None) => Ok(*found),
+            (Some(_), Some(_)) => Err(SanError::AmbiguousMove(original.to_string())),
+            (None, _) => Err(SanError::IllegalMove(original.to_string())),
+        }
+    }
+
+    fn disambiguation(&self, to_move: &Move) -> String {
+        let others: Vec<&Move> = self
+            .valid_moves
+            .iter()
+            .filter(|other| {
+                other.piece_moved == to_move.piece_moved
+                    && other.end == to_move.end
+                    && other.start != to_move.start
+            })
+            .collect();
+
+        if others.is_empty() {
+            String::new()
+        } else if others
+            .iter()
+            .all(|other| other.start.col() != to_move.start.col())
+        {
+            file_name(to_move.start).to_string()
+        } else if others
+            .iter()
+            .all(|other| other.start.row() != to_move.start.row())
+        {
+            (8 - to_move.start.row()).to_string()
+        } else {
+            to_move.start.to_string()
+        }
+    }
+}
+
+fn file_name(coordinates: BoardCoordinates) -> char {
+    (b'a' + coordinates.col() as u8) as char
+}
+
+fn piece_letter(piece: Piece) -> char {
+    match piece {
+        Piece::Pawn => 'P',
+        Piece::Knight => 'N',
+        Piece::Bishop => 'B',
+        Piece::Rook => 'R',
+        Piece::Queen => 'Q',
+        Piece::King => 'K',
+    }
+}
+
+fn letter_piece(letter: char) -> Option<Piece> {
+    match letter {
+        'P' => Some(Piece::Pawn),
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'R' => Some(Piece::Rook),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SanError;
+    use crate::prelude::*;
+
+    fn san_of_every_move(fen: &str) -> Vec<String> {
+        let game_state = GameState::from_fen(fen).unwrap();
+        let mut moves: Vec<String> = game_state
+            .get_valid_moves()
+            .iter()
+            .map(|valid_move| game_state.move_to_san(valid_move))
+            .collect();
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn round_trips_every_legal_move() {
+        let positions = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+        for fen in positions {
+            let game_state = GameState::from_fen(fen).unwrap();
+            for valid_move in game_state.get_valid_moves() {
+                let san = game_state.move_to_san(valid_move);
+                let parsed = game_state.parse_san(&san).unwrap();
+                assert_eq!(parsed, *valid_move, "{san}");
+            }
+        }
+    }
+
+    #[test]
+    fn disambiguation() {
+        let moves = san_of_every_move("4k3/8/8/8/8/8/R3K3/RN3N2 w - - 0 1");
+        assert!(moves.contains(&String::from("Nbd2")));
+        assert!(moves.contains(&String::from("Nfd2")));
+        assert!(moves.contains(&String::from("Ra3")));
+
+        let moves = san_of_every_move("4k3/8/8/8/8/8/4K3/4R1R1 w - - 0 1");
+        assert!(moves.contains(&String::from("Ref1")));
+        assert!(moves.contains(&String::from("Rgf1")));
+
+        let moves = san_of_every_move("k7/8/8/8/8/4R3/8/K3R3 w - - 0 1");
+        assert!(moves.contains(&String::from("R1e2")));
+        assert!(moves.contains(&String::from("R3e2")));
+
+        let moves = san_of_every_move("7k/8/8/8/Q1Q5/8/Q7/K7 w - - 0 1");
+        assert!(moves.contains(&String::from("Qa4b3")));
+        assert!(moves.contains(&String::from("Qcb3")));
+        assert!(moves.contains(&String::from("Q2b3")));
+    }
+
+    #[test]
+    fn special_moves_and_suffixes() {
+        let moves = san_of_every_move("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(moves.contains(&String::from("O-O")));
+        assert!(moves.contains(&String::from("O-O-O")));
+        assert!(moves.contains(&String::from("Rxa8+")));
+
+        let moves = san_of_every_move("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(moves.contains(&String::from("a8=Q")));
+        assert!(moves.contains(&String::from("axb8=Q+")));
+        assert!(moves.contains(&String::from("axb8=N")));
+
+        let moves = san_of_every_move("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        assert!(moves.contains(&String::from("exd6")));
+
+        let moves = san_of_every_move("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1");
+        assert!(moves.contains(&String::from("Ra8#")));
+    }
+
+    #[test]
+    fn lenient_parsing() {
+        let game_state = GameState::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotion = game_state.parse_san("a8=Q").unwrap();
+        assert_eq!(game_state.parse_san("a8Q").unwrap(), promotion);
+        assert_eq!(game_state.parse_san("a7a8=Q!?").unwrap(), promotion);
+        assert_eq!(
+            promotion.special_move,
+            Some(SpecialMove::PawnPromotion(Square::Occupied(
+                Player::White,
+                Piece::Queen
+            )))
+        );
+
+        let game_state = GameState::new();
+        assert!(game_state.parse_san("Ng1f3").is_ok());
+        assert!(game_state.parse_san("0-0").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/RN2KN2 w - - 0 1").unwrap();
+        assert_eq!(
+            game_state.parse_san("Nd2"),
+            Err(SanError::AmbiguousMove(String::from("Nd2")))
+        );
+        assert_eq!(
+            game_state.parse_san("Nd3"),
+            Err(SanError::IllegalMove(String::from("Nd3")))
+        );
+        assert_eq!(
+            game_state.parse_san("O-O"),
+            Err(SanError::IllegalMove(String::from("O-O")))
+        );
+        assert_eq!(
+            game_state.parse_san("Zd2"),
+            Err(SanError::InvalidSyntax(String::from("Zd2")))
+        );
+        assert_eq!(
+            game_state.parse_san("Ni9"),
+            Err(SanError::InvalidSyntax(String::from("Ni9")))
+        );
+    }
+}
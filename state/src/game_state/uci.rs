@@ -0,0 +1,123 @@
+use std::fmt;
+
+use crate::prelude::*;
+
+/// Reason why a move in UCI long algebraic notation was rejected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UciMoveError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+}
+
+impl fmt::Display for UciMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSyntax(uci) => write!(f, "`{uci}` is not a move in UCI notation"),
+            Self::IllegalMove(uci) => write!(f, "`{uci}` is not a legal move"),
+        }
+    }
+}
+
+impl std::error::Error for UciMoveError {}
+
+impl GameState {
+    /// Finds the legal move written in UCI long algebraic notation, like `e1g1` or `a7a8n`.
+    ///
+    /// The returned move carries the right [`SpecialMove`], so castling, en passant and
+    /// promotions can be played with [`GameState::make_new_move`] directly.
+    pub fn parse_uci_move(&self, uci: &str) -> Result<Move, UciMoveError> {
+        let invalid_syntax = || UciMoveError::InvalidSyntax(uci.to_string());
+
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(invalid_syntax());
+        }
+        let start: BoardCoordinates = uci[0..2].parse().map_err(|_| invalid_syntax())?;
+        let end: BoardCoordinates = uci[2..4].parse().map_err(|_| invalid_syntax())?;
+        let promotion = match uci.as_bytes().get(4) {
+            None => None,
+            Some(b'q') => Some(Piece::Queen),
+            Some(b'r') => Some(Piece::Rook),
+            Some(b'b') => Some(Piece::Bishop),
+            Some(b'n') => Some(Piece::Knight),
+            Some(_) => return Err(invalid_syntax()),
+        };
+
+        self.valid_moves
+            .iter()
+            .copied()
+            .find(|valid_move| {
+                let promotion_matches = match valid_move.special_move {
+                    Some(SpecialMove::PawnPromotion(Square::Occupied(_, promoted))) => {
+                        promotion == Some(promoted)
+                    }
+                    _ => promotion.is_none(),
+                };
+                valid_move.start == start && valid_move.end == end && promotion_matches
+            })
+            .ok_or_else(|| UciMoveError::IllegalMove(uci.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UciMoveError;
+    use crate::prelude::*;
+
+    #[test]
+    fn round_trips_every_legal_move() {
+        let positions = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ];
+        for fen in positions {
+            let game_state = GameState::from_fen(fen).unwrap();
+            for valid_move in game_state.get_valid_moves() {
+                let uci = valid_move.to_uci();
+                assert_eq!(game_state.parse_uci_move(&uci), Ok(*valid_move), "{uci}");
+            }
+        }
+    }
+
+    #[test]
+    fn special_moves() {
+        let game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let castle = game_state.parse_uci_move("e1g1").unwrap();
+        assert_eq!(castle.special_move, Some(SpecialMove::Castle));
+        assert_eq!(castle.to_uci(), "e1g1");
+
+        let game_state = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let en_passant = game_state.parse_uci_move("e5d6").unwrap();
+        assert_eq!(en_passant.special_move, Some(SpecialMove::EnPassant));
+
+        let game_state = GameState::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotion = game_state.parse_uci_move("a7a8n").unwrap();
+        assert_eq!(
+            promotion.special_move,
+            Some(SpecialMove::PawnPromotion(Square::Occupied(
+                Player::White,
+                Piece::Knight
+            )))
+        );
+        assert_eq!(promotion.to_uci(), "a7a8n");
+        assert_eq!(
+            game_state.parse_uci_move("a7a8"),
+            Err(UciMoveError::IllegalMove(String::from("a7a8")))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let game_state = GameState::new();
+        assert_eq!(
+            game_state.parse_uci_move("e2e5"),
+            Err(UciMoveError::IllegalMove(String::from("e2e5")))
+        );
+        for uci in ["", "e2", "e2e4qq", "e2e9", "e2e4x", "é2e4"] {
+            assert_eq!(
+                game_state.parse_uci_move(uci),
+                Err(UciMoveError::InvalidSyntax(uci.to_string()))
+            );
+        }
+    }
+}
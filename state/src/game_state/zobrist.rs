@@ -0,0 +1,241 @@
+use crate::prelude::*;
+
+/// Random numbers XORed together to form the Zobrist key of a position.
+struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    /// White king side, white queen side, black king side, black queen side.
+    castling: [u64; 4],
+    en_passant_file: [u64; 8],
+}
+
+/// Generated at compile time from a fixed seed, so keys are the same on every run and can be
+/// stored on disk.
+const KEYS: ZobristKeys = generate_keys();
+
+const fn generate_keys() -> ZobristKeys {
+    // SplitMix64
+    const fn next(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    let mut keys = ZobristKeys {
+        pieces: [[[0; 64]; 6]; 2],
+        black_to_move: 0,
+        castling: [0; 4],
+        en_passant_file: [0; 8],
+    };
+
+    let mut player = 0;
+    while player < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                keys.pieces[player][piece][square] = next(&mut state);
+                square += 1;
+            }
+            piece += 1;
+        }
+        player += 1;
+    }
+    keys.black_to_move = next(&mut state);
+    let mut i = 0;
+    while i < 4 {
+        keys.castling[i] = next(&mut state);
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 8 {
+        keys.en_passant_file[i] = next(&mut state);
+        i += 1;
+    }
+
+    keys
+}
+
+pub(super) fn square_key(coordinates: BoardCoordinates, square: Square) -> u64 {
+    match square {
+        Square::Empty => 0,
+        Square::Occupied(player, piece) => {
+            let player = match player {
+                Player::White => 0,
+                Player::Black => 1,
+            };
+            let piece = match piece {
+                Piece::Pawn => 0,
+                Piece::Knight => 1,
+                Piece::Bishop => 2,
+                Piece::Rook => 3,
+                Piece::Queen => 4,
+                Piece::King => 5,
+            };
+            KEYS.pieces[player][piece][coordinates.row() * 8 + coordinates.col()]
+        }
+    }
+}
+
+pub(super) fn turn_key(turn: Player) -> u64 {
+    match turn {
+        Player::White => 0,
+        Player::Black => KEYS.black_to_move,
+    }
+}
+
+pub(super) fn castling_key(castling_rights: CastlingRights) -> u64 {
+    let rights = [
+        castling_rights.get_white_king_side(),
+        castling_rights.get_white_queen_side(),
+        castling_rights.get_black_king_side(),
+        castling_rights.get_black_queen_side(),
+    ];
+    rights
+        .into_iter()
+        .zip(KEYS.castling)
+        .filter(|(right, _)| *right)
+        .fold(0, |key, (_, right_key)| key ^ right_key)
+}
+
+impl Position {
+    /// Zobrist key of the current position, covering the pieces, the side to move, the
+    /// castling rights and the en passant file.
+    ///
+    /// Kept up to date incrementally as moves are made. The en passant file only
+    /// counts when a pawn stands next to the pawn that just moved two squares, so positions
+    /// where the capture is impossible share a key.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Computes the key from scratch, for a newly set up position.
+    pub(super) fn compute_hash(&self) -> u64 {
+        let mut hash =
+            turn_key(self.turn) ^ castling_key(self.castling_rights) ^ self.en_passant_key();
+        for row in 0..8 {
+            for col in 0..8 {
+                let coordinates = BoardCoordinates::new(row, col);
+                hash ^= square_key(coordinates, self.get_square(coordinates));
+            }
+        }
+        hash
+    }
+
+    pub(super) fn en_passant_key(&self) -> u64 {
+        let Some(en_passant_square) = self.en_passant_square else {
+            return 0;
+        };
+        // The pawn that can capture stands on the same rank as the pawn that moved two squares
+        let (pawn_row, capturer) = match en_passant_square.row() {
+            2 => (3, Player::White),
+            5 => (4, Player::Black),
+            _ => return 0,
+        };
+        let col = en_passant_square.col();
+        let can_capture = [col.checked_sub(1), Some(col + 1).filter(|col| *col < 8)]
+            .into_iter()
+            .flatten()
+            .any(|col| {
+                self.get_square(BoardCoordinates::new(pawn_row, col))
+                    == Square::Occupied(capturer, Piece::Pawn)
+            });
+        if can_capture {
+            KEYS.en_passant_file[col]
+        } else {
+            0
+        }
+    }
+
+    /// Changes the en passant square, keeping the key in sync.
+    ///
+    /// Must be called while the board matches the position the square belongs to.
+    pub(super) fn set_en_passant_square(&mut self, en_passant_square: Option<BoardCoordinates>) {
+        self.hash ^= self.en_passant_key();
+        self.en_passant_square = en_passant_square;
+        self.hash ^= self.en_passant_key();
+    }
+}
+
+impl GameState {
+    /// Zobrist key of the current position, see [`Position::hash`].
+    pub fn hash(&self) -> u64 {
+        self.position.hash()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn play(game_state: &mut GameState, moves: &[&str]) {
+        for uci in moves {
+            let to_move = game_state.parse_uci_move(uci).unwrap();
+            game_state.make_new_move(to_move);
+            assert_eq!(
+                game_state.get_position().hash(),
+                game_state.get_position().compute_hash(),
+                "{uci}"
+            );
+        }
+    }
+
+    #[test]
+    fn incremental_matches_recomputed() {
+        let mut game_state = GameState::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let initial_hash = game_state.get_position().hash();
+        assert_eq!(initial_hash, game_state.get_position().compute_hash());
+
+        // Castling both ways, a rook capture, a double push allowing en passant, the capture
+        // itself and a promotion
+        let moves = [
+            "e1g1", "a6b5", "a2a4", "b4a3", "f3f6", "e8c8", "d5e6", "h3g2", "e6f7", "g2f1q",
+        ];
+        play(&mut game_state, &moves);
+
+        for _ in moves {
+            game_state.undo_last_move();
+            assert_eq!(
+                game_state.get_position().hash(),
+                game_state.get_position().compute_hash()
+            );
+        }
+        assert_eq!(game_state.get_position().hash(), initial_hash);
+    }
+
+    #[test]
+    fn transpositions_share_a_key() {
+        let mut first = GameState::new();
+        play(&mut first, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let mut second = GameState::new();
+        play(&mut second, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+        assert_eq!(first.hash(), second.hash());
+
+        // Knights back home give the starting position again
+        play(&mut first, &["f3g1", "f6g8", "c3b1", "c6b8"]);
+        assert_eq!(first.get_position().hash(), Position::new().hash());
+    }
+
+    #[test]
+    fn distinguishes_side_castling_and_en_passant() {
+        let hash = |fen| Position::from_fen(fen).unwrap().hash();
+
+        let white = hash("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_ne!(white, hash("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1"));
+        assert_ne!(white, hash("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1"));
+
+        let no_en_passant = hash("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1");
+        assert_ne!(no_en_passant, hash("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"));
+        // Nothing can take on e3, so the square makes no difference
+        assert_eq!(
+            hash("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1"),
+            hash("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1")
+        );
+    }
+}
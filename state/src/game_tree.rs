@@ -0,0 +1,402 @@
+use crate::prelude::*;
+
+/// Refers to one position in a [`GameTree`]. Ids stay valid until their node is deleted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Clone, Debug)]
+struct Node {
+    parent: Option<NodeId>,
+    /// The move leading here from the parent, `None` for the root.
+    played: Option<Move>,
+    /// Continuations from this position. The first one belongs to the main line, the others
+    /// are side variations.
+    children: Vec<NodeId>,
+}
+
+/// A game together with the alternatives tried along the way, for analysis.
+///
+/// Every node is a position reached by the moves from the root to it. The first child of
+/// every node continues the main line, so following first children from the root gives the
+/// game itself.
+#[derive(Clone, Debug)]
+pub struct GameTree {
+    root_state: GameState,
+    /// Deleted nodes leave a hole, so that the ids of the others do not change.
+    nodes: Vec<Option<Node>>,
+    current: NodeId,
+    /// The position at the current node.
+    game_state: GameState,
+}
+
+impl Default for GameTree {
+    fn default() -> Self {
+        Self::new(&GameState::new())
+    }
+}
+
+impl GameTree {
+    /// Starts a tree whose main line is the game played so far, with the last position as the
+    /// current node.
+    ///
+    /// The current node keeps the game exactly as it is, including a result that was not
+    /// decided on the board, such as a resignation. Like every position of the tree, the node
+    /// is set up from its moves again once the tree has left it, and then that result is gone.
+    pub fn new(game_state: &GameState) -> Self {
+        let root_state = GameState::from_fen(game_state.get_initial_fen())
+            .expect("the initial FEN of a game is always valid");
+        let mut tree = Self {
+            game_state: root_state.clone(),
+            root_state,
+            nodes: vec![Some(Node {
+                parent: None,
+                played: None,
+                children: Vec::new(),
+            })],
+            current: NodeId(0),
+        };
+        for played in game_state.get_move_log() {
+            tree.make_move(*played);
+        }
+        tree.game_state = game_state.clone();
+        tree
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    /// The position at the current node.
+    pub fn get_game_state(&self) -> &GameState {
+        &self.game_state
+    }
+
+    /// Whether `node` is part of the tree, that is, it has not been deleted.
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.nodes.get(node.0).is_some_and(Option::is_some)
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.node(node).parent
+    }
+
+    /// Continuations of `node`, the main one first.
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        &self.node(node).children
+    }
+
+    /// The move leading to `node`, or `None` for the root.
+    pub fn get_move(&self, node: NodeId) -> Option<Move> {
+        self.node(node).played
+    }
+
+    /// Whether `node` is on the main line of the whole game.
+    pub fn is_main_line(&self, node: NodeId) -> bool {
+        let mut node = node;
+        while let Some(parent) = self.parent(node) {
+            if self.children(parent)[0] != node {
+                return false;
+            }
+            node = parent;
+        }
+        true
+    }
+
+    /// Moves played from the root to `node`.
+    pub fn moves_to(&self, node: NodeId) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let mut node = node;
+        while let Some(parent) = self.parent(node) {
+            moves.extend(self.get_move(node));
+            node = parent;
+        }
+        moves.reverse();
+        moves
+    }
+
+    /// Moves of the main line of the whole game.
+    pub fn main_line(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let mut node = self.root();
+        while let Some(child) = self.children(node).first() {
+            moves.extend(self.get_move(*child));
+            node = *child;
+        }
+        moves
+    }
+
+    /// Plays one of the valid moves of the current position and goes to the resulting node.
+    ///
+    /// A move that was tried before leads back to its node. A new move starts a side variation,
+    /// unless it is the first move tried from here. Once the game is over at the current node,
+    /// no move can be played and the current node is returned.
+    pub fn make_move(&mut self, to_move: Move) -> NodeId {
+        if self.game_state.is_game_over() {
+            return self.current;
+        }
+        let existing = self.children(self.current).iter().copied().find(|child| {
+            self.get_move(*child).is_some_and(|played| {
+                played == to_move && played.special_move == to_move.special_move
+            })
+        });
+
+        let child = existing.unwrap_or_else(|| {
+            let child = NodeId(self.nodes.len());
+            self.nodes.push(Some(Node {
+                parent: Some(self.current),
+                played: Some(to_move),
+                children: Vec::new(),
+            }));
+            self.node_mut(self.current).children.push(child);
+            child
+        });
+
+        self.game_state.make_new_move(to_move);
+        self.current = child;
+        child
+    }
+
+    /// Makes `node` the current node, setting up its position.
+    ///
+    /// Moves are taken back up to the last node the current one shares with the way to `node`,
+    /// and only the moves from there on are played again.
+    pub fn go_to(&mut self, node: NodeId) {
+        let mut on_the_way = vec![false; self.nodes.len()];
+        let mut step = Some(node);
+        while let Some(current) = step {
+            on_the_way[current.0] = true;
+            step = self.parent(current);
+        }
+        while !on_the_way[self.current.0] {
+            self.go_back();
+        }
+
+        let mut forward = Vec::new();
+        let mut step = node;
+        while step != self.current {
+            forward.push(step);
+            step = self
+                .parent(step)
+                .expect("the current node is on the way to `node`");
+        }
+        for child in forward.into_iter().rev() {
+            let played = self.get_move(child).expect("only the root has no move");
+            self.game_state.make_new_move(played);
+        }
+        self.current = node;
+    }
+
+    /// Goes to the parent of the current node. Returns `false` at the root.
+    pub fn go_back(&mut self) -> bool {
+        match self.parent(self.current) {
+            Some(parent) => {
+                self.game_state.undo_last_move();
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Goes to the main continuation of the current node. Returns `false` at the end of a line.
+    pub fn go_forward(&mut self) -> bool {
+        match self.children(self.current).first() {
+            Some(child) => {
+                let child = *child;
+                let played = self.get_move(child).expect("only the root has no move");
+                self.game_state.make_new_move(played);
+                self.current = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes the line through `node` the main line, at every branch between it and the root.
+    pub fn promote_to_main_line(&mut self, node: NodeId) {
+        let mut node = node;
+        while let Some(parent) = self.parent(node) {
+            let children = &mut self.node_mut(parent).children;
+            let index = children
+                .iter()
+                .position(|child| *child == node)
+                .expect("every node is a child of its parent");
+            let promoted = children.remove(index);
+            children.insert(0, promoted);
+            node = parent;
+        }
+    }
+
+    /// Removes `node` and everything that follows it. If the current node was among them, the
+    /// parent of `node` becomes the current node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is the root, which cannot be deleted.
+    pub fn delete_branch(&mut self, node: NodeId) {
+        let parent = self
+            .parent(node)
+            .expect("the root of a game tree cannot be deleted");
+        if self.is_ancestor(node, self.current) {
+            self.go_to(parent);
+        }
+        self.node_mut(parent)
+            .children
+            .retain(|child| *child != node);
+
+        let mut pending = vec![node];
+        while let Some(removed) = pending.pop() {
+            if let Some(removed) = self.nodes[removed.0].take() {
+                pending.extend(removed.children);
+            }
+        }
+    }
+
+    /// Whether `ancestor` is `node` itself or on the way from the root to it.
+    fn is_ancestor(&self, ancestor: NodeId, node: NodeId) -> bool {
+        let mut node = Some(node);
+        while let Some(current) = node {
+            if current == ancestor {
+                return true;
+            }
+            node = self.parent(current);
+        }
+        false
+    }
+
+    /// The position at the root of the tree.
+    pub(crate) fn get_root_state(&self) -> &GameState {
+        &self.root_state
+    }
+
+    fn node(&self, node: NodeId) -> &Node {
+        self.nodes[node.0]
+            .as_ref()
+            .expect("the node has been deleted from the game tree")
+    }
+
+    fn node_mut(&mut self, node: NodeId) -> &mut Node {
+        self.nodes[node.0]
+            .as_mut()
+            .expect("the node has been deleted from the game tree")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(tree: &mut GameTree, moves: &[&str]) -> NodeId {
+        for uci in moves {
+            let to_move = tree.get_game_state().parse_uci_move(uci).unwrap();
+            tree.make_move(to_move);
+        }
+        tree.current()
+    }
+
+    fn uci(moves: Vec<Move>) -> Vec<String> {
+        moves.iter().map(Move::to_uci).collect()
+    }
+
+    #[test]
+    fn variations_keep_the_main_line() {
+        let mut tree = GameTree::default();
+        play(&mut tree, &["e2e4", "e7e5", "g1f3"]);
+        tree.go_back();
+        tree.go_back();
+        let sicilian = play(&mut tree, &["c7c5", "g1f3"]);
+
+        assert_eq!(uci(tree.main_line()), ["e2e4", "e7e5", "g1f3"]);
+        assert_eq!(uci(tree.moves_to(sicilian)), ["e2e4", "c7c5", "g1f3"]);
+        assert!(!tree.is_main_line(sicilian));
+        assert_eq!(tree.get_game_state().get_move_log().len(), 3);
+
+        // Playing a known move follows the existing node
+        let e4 = tree.children(tree.root())[0];
+        tree.go_to(e4);
+        assert_eq!(tree.children(e4).len(), 2);
+        play(&mut tree, &["c7c5"]);
+        assert_eq!(tree.children(e4).len(), 2);
+        assert!(tree.go_forward());
+        assert_eq!(tree.current(), sicilian);
+        assert!(!tree.go_forward());
+    }
+
+    #[test]
+    fn navigating_matches_replaying() {
+        let replayed = |tree: &GameTree| {
+            let mut game_state = GameState::new();
+            for played in tree.moves_to(tree.current()) {
+                game_state.make_new_move(played);
+            }
+            game_state
+        };
+
+        let mut tree = GameTree::default();
+        let open_game = play(&mut tree, &["e2e4", "e7e5", "g1f3", "b8c6"]);
+        tree.go_to(tree.root());
+        let queens_gambit = play(&mut tree, &["d2d4", "d7d5", "c2c4"]);
+
+        tree.go_to(open_game);
+        assert_eq!(*tree.get_game_state(), replayed(&tree));
+        while tree.go_back() {
+            assert_eq!(*tree.get_game_state(), replayed(&tree));
+        }
+        tree.go_to(queens_gambit);
+        assert_eq!(*tree.get_game_state(), replayed(&tree));
+
+        // Deleting the line of the current node goes back to where the line started
+        let d5 = tree.parent(queens_gambit).unwrap();
+        let d4 = tree.parent(d5).unwrap();
+        tree.delete_branch(d5);
+        assert_eq!(tree.current(), d4);
+        assert_eq!(*tree.get_game_state(), replayed(&tree));
+    }
+
+    #[test]
+    fn promoting_and_deleting_variations() {
+        let mut tree = GameTree::default();
+        play(&mut tree, &["e2e4", "e7e5"]);
+        tree.go_to(tree.root());
+        let d4 = play(&mut tree, &["d2d4"]);
+        let d5 = play(&mut tree, &["d7d5"]);
+
+        tree.promote_to_main_line(d5);
+        assert_eq!(uci(tree.main_line()), ["d2d4", "d7d5"]);
+        assert!(tree.is_main_line(d5));
+
+        tree.delete_branch(d4);
+        assert!(!tree.contains(d4));
+        assert!(!tree.contains(d5));
+        assert_eq!(tree.current(), tree.root());
+        assert_eq!(tree.get_game_state().get_move_log().len(), 0);
+        assert_eq!(uci(tree.main_line()), ["e2e4", "e7e5"]);
+    }
+
+    #[test]
+    fn starts_from_a_game() {
+        let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        for uci in ["e1c1", "e8e7"] {
+            let to_move = game_state.parse_uci_move(uci).unwrap();
+            game_state.make_new_move(to_move);
+        }
+        game_state.resign(Player::White).unwrap();
+        let mut tree = GameTree::new(&game_state);
+        assert_eq!(tree.get_game_state().to_fen(), game_state.to_fen());
+        assert_eq!(
+            tree.get_game_state().get_game_result(),
+            game_state.get_game_result()
+        );
+        assert!(tree.go_back());
+        assert!(tree.go_back());
+        assert!(!tree.go_back());
+        assert_eq!(
+            tree.get_game_state().to_fen(),
+            "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1"
+        );
+    }
+}
@@ -1,11 +1,31 @@
+mod bitboard;
 mod board;
+mod clock;
 mod components;
+mod eval;
 mod game_state;
+mod game_tree;
 mod moves;
+mod pgn;
+mod search;
 
 pub mod prelude {
-    pub use crate::board::{BoardCoordinates, INITIAL_POSITION};
-    pub use crate::components::{CastlingRights, GameResult, Piece, Player, Square};
-    pub use crate::game_state::GameState;
+    pub use crate::board::{
+        BoardCoordinates, ParseCoordinatesError, SquareSet, Squares, INITIAL_POSITION,
+    };
+    pub use crate::clock::{
+        Clock, ClockError, Increment, ParseTimeControlError, Stage, TimeControl,
+    };
+    pub use crate::components::{CastlingRights, GameResult, Piece, Player, Square, Termination};
+    pub use crate::eval::{evaluate, evaluate_position};
+    pub use crate::game_state::{
+        FenError, GameState, IllegalMoveError, OutcomeError, Position, PositionBuilder,
+        PositionError, SanError, UciMoveError,
+    };
+    pub use crate::game_tree::{GameTree, NodeId};
     pub use crate::moves::{Move, MoveCounter, SpecialMove};
+    pub use crate::pgn::{parse_pgn, PgnError, PgnErrorKind, PgnGame, PgnTags};
+    pub use crate::search::{
+        SearchLimits, SearchResult, TranspositionTable, DEFAULT_HASH_MB, MATE_SCORE, MAX_DEPTH,
+    };
 }
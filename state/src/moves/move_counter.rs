@@ -1,5 +1,8 @@
+use crate::prelude::Player;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct MoveCounter {
-    fifty_move_counter: Vec<u8>,
+    fifty_move_rule_count: u16,
     halfmove: u128,
     fullmove: u128,
 }
@@ -13,45 +16,37 @@ impl Default for MoveCounter {
 impl MoveCounter {
     pub fn new() -> Self {
         Self {
-            fifty_move_counter: vec![0],
+            fifty_move_rule_count: 0,
             halfmove: 0,
             fullmove: 1,
         }
     }
+    pub fn from_counts(fifty_move_rule_count: u16, fullmove: u128, turn: Player) -> Self {
+        let fullmove = fullmove.max(1);
+        let halfmove = (fullmove - 1) * 2
+            + match turn {
+                Player::White => 0,
+                Player::Black => 1,
+            };
+        Self {
+            fifty_move_rule_count,
+            halfmove,
+            fullmove,
+        }
+    }
     pub fn increment(&mut self, reset_fifty_move_rule: bool) {
         if reset_fifty_move_rule {
-            self.reset_fifty_move_rule();
-        } else if let Some(count) = self.fifty_move_counter.last_mut() {
-            *count = count.saturating_add(1);
+            self.fifty_move_rule_count = 0;
+        } else {
+            self.fifty_move_rule_count = self.fifty_move_rule_count.saturating_add(1);
         }
         self.halfmove = self.halfmove.saturating_add(1);
-        if self.halfmove % 2 == 0 {
+        if self.halfmove.is_multiple_of(2) {
             self.fullmove = self.fullmove.saturating_add(1);
         }
     }
-
-    fn reset_fifty_move_rule(&mut self) {
-        self.fifty_move_counter.push(0);
-    }
-    pub fn decrement(&mut self) {
-        if let Some(count) = self.fifty_move_counter.last_mut() {
-            if *count > 0 {
-                *count -= 1;
-            } else if self.fifty_move_counter.len() > 1 {
-                self.fifty_move_counter.pop();
-            }
-        }
-        self.halfmove = self.halfmove.saturating_sub(1);
-        if self.halfmove % 2 == 0 && self.fullmove > 0 {
-            self.fullmove = self.fullmove.saturating_sub(1);
-        }
-    }
-    pub fn get_fifty_move_rule_count(&self) -> u8 {
-        if let Some(count) = self.fifty_move_counter.last() {
-            *count
-        } else {
-            0
-        }
+    pub fn get_fifty_move_rule_count(&self) -> u16 {
+        self.fifty_move_rule_count
     }
     pub fn get_halfmove_count(&self) -> u128 {
         self.halfmove
@@ -71,20 +66,6 @@ mod tests {
         for _ in 0..1_000_000 {
             counter.increment(false);
         }
-        assert_eq!(255u8, counter.get_fifty_move_rule_count());
-    }
-    #[test]
-    fn reset_undo() {
-        let mut counter = MoveCounter::default();
-        for _ in 0..10 {
-            counter.reset_fifty_move_rule();
-            for _ in 0..60 {
-                counter.increment(false);
-            }
-        }
-        for _ in 0..1_000_000 {
-            counter.decrement();
-        }
-        assert_eq!(counter.fifty_move_counter.len(), 1);
+        assert_eq!(u16::MAX, counter.get_fifty_move_rule_count());
     }
 }
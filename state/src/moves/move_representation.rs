@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct Move {
     pub start: BoardCoordinates,
     pub end: BoardCoordinates,
@@ -32,10 +32,10 @@ impl Move {
         end: BoardCoordinates,
 
         special_move: Option<SpecialMove>,
-        game_state: &GameState,
+        position: &Position,
     ) -> Self {
-        let piece_moved = game_state.get_square(start);
-        let piece_captured = game_state.get_square(end);
+        let piece_moved = position.get_square(start);
+        let piece_captured = position.get_square(end);
         Self {
             start,
             end,
@@ -46,4 +46,18 @@ impl Move {
             special_move,
         }
     }
+
+    /// Writes the move in the long algebraic notation used by UCI, like `e2e4` or `e7e8q`.
+    pub fn to_uci(&self) -> String {
+        let mut uci = format!("{}{}", self.start, self.end);
+        if let Some(SpecialMove::PawnPromotion(Square::Occupied(_, piece))) = self.special_move {
+            uci.push(match piece {
+                Piece::Knight => 'n',
+                Piece::Bishop => 'b',
+                Piece::Rook => 'r',
+                _ => 'q',
+            });
+        }
+        uci
+    }
 }
@@ -1,6 +1,6 @@
 use crate::prelude::Square;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SpecialMove {
     EnPassant,
     Castle,
@@ -0,0 +1,615 @@
+use std::fmt;
+
+use crate::prelude::*;
+
+const STANDARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Tag pairs of a PGN game, kept in the order they will be written.
+///
+/// A new set of tags always starts with the seven-tag roster.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PgnTags {
+    tags: Vec<(String, String)>,
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgnTags {
+    pub fn new() -> Self {
+        let tags = [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", "*"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        Self { tags }
+    }
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn set(&mut self, name: &str, value: &str) {
+        if let Some((_, old_value)) = self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            *old_value = value.to_string();
+        } else {
+            self.tags.push((name.to_string(), value.to_string()));
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+/// A single game read from a PGN file.
+#[derive(Clone)]
+pub struct PgnGame {
+    pub tags: PgnTags,
+    pub game_state: GameState,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PgnErrorKind {
+    InvalidTag,
+    InvalidFen(FenError),
+    InvalidMove(SanError),
+    UnexpectedToken,
+    UnterminatedComment,
+    UnterminatedVariation,
+}
+
+/// Reason why a PGN file could not be read, with the place where it happened.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PgnError {
+    kind: PgnErrorKind,
+    game: usize,
+    ply: usize,
+    token: String,
+}
+
+impl PgnError {
+    pub fn kind(&self) -> &PgnErrorKind {
+        &self.kind
+    }
+    /// Index of the game inside the file, starting at 1.
+    pub fn game(&self) -> usize {
+        self.game
+    }
+    /// Index of the half-move being read when the error happened, starting at 1.
+    ///
+    /// Errors inside the tag section report ply 0.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match &self.kind {
+            PgnErrorKind::InvalidTag => String::from("invalid tag"),
+            PgnErrorKind::InvalidFen(error) => format!("invalid FEN tag: {error}"),
+            PgnErrorKind::InvalidMove(error) => format!("{error}"),
+            PgnErrorKind::UnexpectedToken => String::from("unexpected token"),
+            PgnErrorKind::UnterminatedComment => String::from("unterminated comment"),
+            PgnErrorKind::UnterminatedVariation => String::from("unterminated variation"),
+        };
+        write!(
+            f,
+            "game {}, ply {}: {reason} `{}`",
+            self.game, self.ply, self.token
+        )
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl GameState {
+    /// Writes the game in Portable Game Notation, using Standard Algebraic Notation for the
+    /// movetext.
+    ///
+    /// The `Result` tag is taken from the game if it is over, and from `tags` otherwise. Games
+    /// that did not start from the initial position get `SetUp` and `FEN` tags.
+    pub fn to_pgn(&self, tags: &PgnTags) -> String {
+        let mut tags = tags.clone();
+        // Results that are not decided on the board, like a resignation, would be lost when
+        // the moves are replayed
+        if let Some(game_result) = self.get_game_result() {
+            tags.set("Result", game_result.pgn_result());
+        }
+        GameTree::new(self).to_pgn(&tags)
+    }
+}
+
+impl GameTree {
+    /// Writes the tree in Portable Game Notation, with side variations in parentheses after
+    /// the move they replace.
+    ///
+    /// The `Result` tag is taken from the end of the main line if the game is over there, and
+    /// from `tags` otherwise.
+    pub fn to_pgn(&self, tags: &PgnTags) -> String {
+        let root_state = self.get_root_state();
+        let initial_fen = root_state.to_fen();
+
+        let mut main_line_end = root_state.clone();
+        for played in self.main_line() {
+            main_line_end.make_new_move(played);
+        }
+
+        let mut tags = tags.clone();
+        if let Some(game_result) = main_line_end.get_game_result() {
+            tags.set("Result", game_result.pgn_result());
+        }
+        if initial_fen != STANDARD_FEN {
+            tags.set("SetUp", "1");
+            tags.set("FEN", &initial_fen);
+        }
+
+        let mut pgn = String::new();
+        for (name, value) in tags.iter() {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn.push_str(&format!("[{name} \"{value}\"]\n"));
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        self.push_line(self.root(), &mut root_state.clone(), true, &mut tokens);
+        tokens.push(tags.get("Result").unwrap_or("*").to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + token.len() + 1 > 79 {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            pgn.push_str(&token);
+        }
+        pgn.push('\n');
+
+        pgn
+    }
+
+    /// Writes the main continuation of `node` to the end, where `game_state` is the position at
+    /// `node`. The moves of every side variation follow the main move they replace.
+    fn push_line(
+        &self,
+        node: NodeId,
+        game_state: &mut GameState,
+        needs_number: bool,
+        tokens: &mut Vec<String>,
+    ) {
+        let mut node = node;
+        let mut needs_number = needs_number;
+        while let Some((main, variations)) = self.children(node).split_first() {
+            let played = self.get_move(*main).expect("only the root has no move");
+            push_move(game_state, &played, needs_number, tokens);
+
+            for variation in variations {
+                let played = self
+                    .get_move(*variation)
+                    .expect("only the root has no move");
+                let start = tokens.len();
+                let mut variation_state = game_state.clone();
+                push_move(&variation_state, &played, true, tokens);
+                variation_state.make_new_move(played);
+                self.push_line(*variation, &mut variation_state, false, tokens);
+
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+            }
+
+            game_state.make_new_move(played);
+            // Black moves need their number again after a variation interrupted the line
+            needs_number = !variations.is_empty();
+            node = *main;
+        }
+    }
+}
+
+/// Writes `played` in SAN, after the move number when white moves or `needs_number` is set.
+fn push_move(game_state: &GameState, played: &Move, needs_number: bool, tokens: &mut Vec<String>) {
+    let fullmove = game_state.get_move_counter().get_fullmove_count();
+    if game_state.get_turn() == Player::White {
+        tokens.push(format!("{fullmove}."));
+    } else if needs_number {
+        tokens.push(format!("{fullmove}..."));
+    }
+    tokens.push(game_state.move_to_san(played));
+}
+
+#[derive(PartialEq)]
+enum Token<'a> {
+    Tag(&'a str),
+    Comment,
+    Nag,
+    VariationStart,
+    VariationEnd,
+    MoveNumber,
+    Result,
+    San(&'a str),
+}
+
+/// Reads every game of a PGN file, replaying the moves to rebuild each game.
+///
+/// Comments, numeric annotation glyphs and variations are skipped.
+pub fn parse_pgn(input: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = Vec::new();
+
+    let mut tags = PgnTags::new();
+    let mut game_state = GameState::new();
+    let mut in_movetext = false;
+    let mut has_content = false;
+    let mut variation_depth = 0;
+
+    let mut rest = input;
+    loop {
+        let game = games.len() + 1;
+        let ply = |game_state: &GameState| {
+            if in_movetext {
+                game_state.get_move_log().len() + 1
+            } else {
+                0
+            }
+        };
+        let error = |kind, ply, token: &str| PgnError {
+            kind,
+            game,
+            ply,
+            token: token.to_string(),
+        };
+
+        let (token, text, remaining) = match next_token(rest) {
+            Some(Ok(next)) => next,
+            Some(Err((kind, text))) => return Err(error(kind, ply(&game_state), text)),
+            None => break,
+        };
+        rest = remaining;
+
+        if variation_depth > 0 {
+            match token {
+                Token::VariationStart => variation_depth += 1,
+                Token::VariationEnd => variation_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match token {
+            Token::Tag(content) => {
+                if in_movetext {
+                    games.push(PgnGame {
+                        tags: std::mem::take(&mut tags),
+                        game_state: std::mem::take(&mut game_state),
+                    });
+                    in_movetext = false;
+                }
+                let (name, value) =
+                    parse_tag(content).ok_or_else(|| error(PgnErrorKind::InvalidTag, 0, text))?;
+                if name == "FEN" {
+                    game_state = GameState::from_fen(&value)
+                        .map_err(|fen_error| error(PgnErrorKind::InvalidFen(fen_error), 0, text))?;
+                }
+                tags.set(name, &value);
+                has_content = true;
+            }
+            Token::San(san) => {
+                has_content = true;
+                let played = game_state.parse_san(san).map_err(|san_error| {
+                    error(
+                        PgnErrorKind::InvalidMove(san_error),
+                        game_state.get_move_log().len() + 1,
+                        text,
+                    )
+                })?;
+                in_movetext = true;
+                game_state.make_new_move(played);
+            }
+            Token::Result => {
+                tags.set("Result", text);
+                games.push(PgnGame {
+                    tags: std::mem::take(&mut tags),
+                    game_state: std::mem::take(&mut game_state),
+                });
+                in_movetext = false;
+                has_content = false;
+            }
+            Token::VariationStart => {
+                in_movetext = true;
+                variation_depth += 1;
+            }
+            Token::VariationEnd => {
+                return Err(error(PgnErrorKind::UnexpectedToken, ply(&game_state), text));
+            }
+            Token::Comment | Token::Nag | Token::MoveNumber => in_movetext = true,
+        }
+    }
+
+    if variation_depth > 0 {
+        return Err(PgnError {
+            kind: PgnErrorKind::UnterminatedVariation,
+            game: games.len() + 1,
+            ply: game_state.get_move_log().len() + 1,
+            token: String::from("("),
+        });
+    }
+    if has_content {
+        games.push(PgnGame { tags, game_state });
+    }
+
+    Ok(games)
+}
+
+type TokenResult<'a> = Result<(Token<'a>, &'a str, &'a str), (PgnErrorKind, &'a str)>;
+
+/// Splits the next token off `input`, returning the token, its text and the rest of the input.
+fn next_token(input: &str) -> Option<TokenResult<'_>> {
+    let mut input = input;
+    loop {
+        input = input.trim_start();
+        // Lines starting with `%` are escaped and must be ignored
+        if input.starts_with('%') {
+            input = input.split_once('\n').map_or("", |(_, rest)| rest);
+        } else {
+            break;
+        }
+    }
+
+    let first = input.chars().next()?;
+    let split = |length: usize| (&input[..length], &input[length..]);
+
+    let result = match first {
+        '[' => match tag_end(input) {
+            Some(end) => {
+                let (text, rest) = split(end + 1);
+                Ok((Token::Tag(&text[1..end]), text, rest))
+            }
+            None => Err((PgnErrorKind::InvalidTag, input)),
+        },
+        '{' => match input.find('}') {
+            Some(end) => {
+                let (text, rest) = split(end + 1);
+                Ok((Token::Comment, text, rest))
+            }
+            None => Err((PgnErrorKind::UnterminatedComment, input)),
+        },
+        ';' => {
+            let end = input.find('\n').unwrap_or(input.len());
+            let (text, rest) = split(end);
+            Ok((Token::Comment, text, rest))
+        }
+        '(' => {
+            let (text, rest) = split(1);
+            Ok((Token::VariationStart, text, rest))
+        }
+        ')' => {
+            let (text, rest) = split(1);
+            Ok((Token::VariationEnd, text, rest))
+        }
+        _ => {
+            let end = input
+                .find(|c: char| c.is_whitespace() || "[]{}();".contains(c))
+                .unwrap_or(input.len());
+            let (mut text, mut rest) = split(end);
+
+            // A move number may be glued to the move that follows it, as in `1.e4`
+            if let Some(dot) = text.rfind('.') {
+                if text[..dot].chars().all(|c| c.is_ascii_digit() || c == '.')
+                    && dot + 1 < text.len()
+                {
+                    (text, rest) = split(dot + 1);
+                }
+            }
+
+            let token = if text.starts_with('$') {
+                Token::Nag
+            } else if matches!(text, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                Token::Result
+            } else if text.starts_with(|c: char| c.is_ascii_digit())
+                && text.chars().all(|c| c.is_ascii_digit() || c == '.')
+            {
+                Token::MoveNumber
+            } else {
+                Token::San(text)
+            };
+            Ok((token, text, rest))
+        }
+    };
+    Some(result)
+}
+
+/// Index of the `]` closing the tag at the start of `input`, skipping any inside the quoted
+/// value.
+fn tag_end(input: &str) -> Option<usize> {
+    let mut in_value = false;
+    let mut escaped = false;
+    for (index, c) in input.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_value => escaped = true,
+            '"' => in_value = !in_value,
+            ']' if !in_value => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_tag(content: &str) -> Option<(&str, String)> {
+    let (name, value) = content.trim().split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.push(chars.next()?);
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    Some((name, unescaped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pgn, PgnErrorKind, PgnTags};
+    use crate::prelude::*;
+
+    const IMMORTAL_GAME: &str = r#"[Event "London"]
+[Site "London ENG"]
+[Date "1851.06.21"]
+[Round "?"]
+[White "Adolf Anderssen"]
+[Black "Lionel Kieseritzky"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5 8.
+Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5 12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15.
+Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6 Bxg1 19. e5 Qxa1+ 20. Ke2 Na6 21.
+Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+
+    #[test]
+    fn round_trip() {
+        let games = parse_pgn(IMMORTAL_GAME).unwrap();
+        assert_eq!(games.len(), 1);
+
+        let game = &games[0];
+        assert_eq!(game.tags.get("White"), Some("Adolf Anderssen"));
+        assert_eq!(game.game_state.get_move_log().len(), 45);
+        assert_eq!(
+            game.game_state.get_game_result(),
+            Some(GameResult::win(Player::White, Termination::Checkmate))
+        );
+        assert_eq!(
+            game.game_state.get_game_result().unwrap().to_string(),
+            "White wins by checkmate"
+        );
+        assert_eq!(game.game_state.to_pgn(&game.tags), IMMORTAL_GAME);
+    }
+
+    #[test]
+    fn multiple_games_with_annotations() {
+        let pgn = r#"[Event "First"]
+[Site "Open [A] \"Hall ]\""]
+
+1.e4 {King's pawn} e5 2. Nf3 $1 (2. f4 exf4 (2... d5) 3. Nf3) Nc6 *
+
+[Event "Second"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+; a line comment
+1. e4 Kd7 2. e5!? Ke6 1/2-1/2
+"#;
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+
+        assert_eq!(games[0].tags.get("Event"), Some("First"));
+        // A `]` inside the value does not end the tag
+        assert_eq!(games[0].tags.get("Site"), Some("Open [A] \"Hall ]\""));
+        assert_eq!(games[0].tags.get("Result"), Some("*"));
+        assert_eq!(games[0].game_state.get_move_log().len(), 4);
+
+        assert_eq!(games[1].tags.get("Event"), Some("Second"));
+        assert_eq!(games[1].tags.get("Result"), Some("1/2-1/2"));
+        assert_eq!(
+            games[1].game_state.to_fen(),
+            "8/8/4k3/4P3/8/8/8/4K3 w - - 1 3"
+        );
+    }
+
+    #[test]
+    fn writes_set_up_positions() {
+        let mut game_state = GameState::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").unwrap();
+        let to_move = game_state.parse_san("Kd7").unwrap();
+        game_state.make_new_move(to_move);
+
+        let mut tags = PgnTags::new();
+        tags.set("White", "Someone \"quoted\"");
+        assert_eq!(
+            game_state.to_pgn(&tags),
+            r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "Someone \"quoted\""]
+[Black "?"]
+[Result "*"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 12"]
+
+12... Kd7 *
+"#
+        );
+        let games = parse_pgn(&game_state.to_pgn(&tags)).unwrap();
+        assert_eq!(games[0].tags.get("White"), Some("Someone \"quoted\""));
+        assert_eq!(games[0].game_state.to_fen(), game_state.to_fen());
+    }
+
+    #[test]
+    fn writes_variations() {
+        let mut tree = GameTree::default();
+        let play = |tree: &mut GameTree, moves: &[&str]| {
+            for san in moves {
+                let to_move = tree.get_game_state().parse_san(san).unwrap();
+                tree.make_move(to_move);
+            }
+        };
+        play(&mut tree, &["e4", "e5", "Nf3"]);
+        let e4 = tree.children(tree.root())[0];
+        tree.go_to(e4);
+        play(&mut tree, &["c5", "Nf3", "d6"]);
+        tree.go_back();
+        tree.go_back();
+        play(&mut tree, &["Nc3"]);
+
+        let pgn = tree.to_pgn(&PgnTags::new());
+        assert!(pgn.ends_with("\n1. e4 e5 (1... c5 2. Nf3 (2. Nc3) 2... d6) 2. Nf3 *\n"));
+
+        // Readers that skip variations still get the main line
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games[0].game_state.get_move_log().len(), 3);
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let pgn = "1. e4 e5 *\n\n1. e4 e5 2. Ke3 *\n";
+        let error = parse_pgn(pgn).err().unwrap();
+        assert_eq!(
+            error.kind(),
+            &PgnErrorKind::InvalidMove(SanError::IllegalMove(String::from("Ke3")))
+        );
+        assert_eq!(error.game(), 2);
+        assert_eq!(error.ply(), 3);
+        assert_eq!(error.token(), "Ke3");
+
+        let error = parse_pgn("[Event \"Broken]\n1. e4 *").err().unwrap();
+        assert_eq!(error.kind(), &PgnErrorKind::InvalidTag);
+        assert_eq!(error.ply(), 0);
+
+        let error = parse_pgn("1. e4 { never closed").err().unwrap();
+        assert_eq!(error.kind(), &PgnErrorKind::UnterminatedComment);
+        assert_eq!(error.ply(), 2);
+    }
+}
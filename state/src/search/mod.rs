@@ -0,0 +1,493 @@
+mod ordering;
+mod transposition;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+use ordering::MoveOrdering;
+use transposition::Bound;
+
+pub use transposition::{TranspositionTable, DEFAULT_HASH_MB};
+
+/// Score of a checkmate delivered on the current move. Mates further away score lower, by one
+/// point per ply.
+pub const MATE_SCORE: i32 = 1_000_000;
+
+/// Deepest iteration the search will ever start.
+pub const MAX_DEPTH: usize = 64;
+
+/// Margin for positional gains on top of the material a capture wins, when deciding whether it
+/// could raise alpha at all.
+const DELTA_MARGIN: i32 = 200;
+
+/// When to stop searching. The search ends as soon as any of the limits is reached.
+#[derive(Clone, Debug)]
+pub struct SearchLimits {
+    pub depth: usize,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    /// Lets another thread end the search early, for example when a UCI `stop` arrives.
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            nodes: None,
+            time: None,
+            stop: None,
+        }
+    }
+}
+
+/// Outcome of the deepest iteration that the search completed.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// Score in centipawns from the point of view of the side to move.
+    pub score: i32,
+    pub depth: usize,
+    pub nodes: u64,
+    pub time: Duration,
+    pub principal_variation: Vec<Move>,
+    /// How full the transposition table is, in permille.
+    pub hashfull: u16,
+    /// Positions whose search ended early because a move was too good for the opponent to
+    /// allow, and how many of them ended on the first move tried.
+    pub cutoffs: u64,
+    pub first_move_cutoffs: u64,
+}
+
+impl SearchResult {
+    /// Number of moves until mate, negative when the side to move is the one getting mated.
+    pub fn mate_in(&self) -> Option<i32> {
+        let plies = MATE_SCORE - self.score.abs();
+        if plies > MAX_DEPTH as i32 {
+            return None;
+        }
+        let moves = (plies + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+
+    /// Share of the cutoffs that came from the first move tried, in percent. The closer to
+    /// 100, the better the moves are ordered. `None` if there were no cutoffs.
+    pub fn first_move_cutoff_percentage(&self) -> Option<f64> {
+        (self.cutoffs > 0).then(|| self.first_move_cutoffs as f64 * 100.0 / self.cutoffs as f64)
+    }
+}
+
+struct Searcher<'a> {
+    limits: &'a SearchLimits,
+    table: &'a mut TranspositionTable,
+    ordering: MoveOrdering,
+    start: Instant,
+    nodes: u64,
+    cutoffs: u64,
+    first_move_cutoffs: u64,
+    stopped: bool,
+}
+
+impl Searcher<'_> {
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+        // Looking at the clock on every node would cost more than the node itself
+        if self.nodes.is_multiple_of(16) {
+            let out_of_time = self
+                .limits
+                .time
+                .is_some_and(|time| self.start.elapsed() >= time);
+            let out_of_nodes = self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes);
+            let told_to_stop = self
+                .limits
+                .stop
+                .as_ref()
+                .is_some_and(|stop| stop.load(Ordering::Relaxed));
+            self.stopped = out_of_time || out_of_nodes || told_to_stop;
+        }
+        self.stopped
+    }
+
+    fn negamax(
+        &mut self,
+        game_state: &mut GameState,
+        depth: usize,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        principal_variation: &mut Vec<Move>,
+    ) -> i32 {
+        principal_variation.clear();
+        if depth == 0 {
+            return self.quiescence(game_state, ply, alpha, beta);
+        }
+        self.nodes += 1;
+
+        if let Some(game_result) = game_state.get_game_result() {
+            return match game_result.get_termination() {
+                Termination::Checkmate => -MATE_SCORE + ply as i32,
+                _ => 0,
+            };
+        }
+        // A draw either side could claim is as good as played out
+        if ply > 0 && game_state.claimable_draw().is_some() {
+            return 0;
+        }
+        if self.should_stop() {
+            return evaluate(game_state);
+        }
+
+        let key = game_state.get_position().hash();
+        let entry = self.table.probe(key);
+        if let Some(entry) = entry.filter(|entry| entry.depth as usize >= depth) {
+            // Exact scores inside the window are searched again, to get the whole variation
+            let score = entry.score(ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => score <= alpha || score >= beta,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                return score.clamp(alpha, beta);
+            }
+        }
+
+        let mut moves = game_state.get_valid_moves().clone();
+        self.ordering.order(
+            &mut moves,
+            game_state.get_position(),
+            entry.and_then(|entry| entry.best_move),
+            ply,
+        );
+
+        let original_alpha = alpha;
+        let mut best_move = None;
+        let mut child_variation = Vec::new();
+        for (index, to_move) in moves.into_iter().enumerate() {
+            game_state.make_new_move(to_move);
+            let score = -self.negamax(
+                game_state,
+                depth - 1,
+                ply + 1,
+                -beta,
+                -alpha,
+                &mut child_variation,
+            );
+            game_state.undo_last_move();
+
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                self.cutoffs += 1;
+                if index == 0 {
+                    self.first_move_cutoffs += 1;
+                }
+                self.ordering
+                    .record_cutoff(to_move, game_state.get_turn(), depth, ply);
+                self.table
+                    .store(key, Some(to_move), depth, beta, Bound::Lower, ply);
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(to_move);
+                principal_variation.clear();
+                principal_variation.push(to_move);
+                principal_variation.append(&mut child_variation);
+            }
+        }
+
+        let bound = if alpha > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(key, best_move, depth, alpha, bound, ply);
+        alpha
+    }
+
+    /// Searches captures and promotions until the position is quiet, so that the evaluation
+    /// never stops in the middle of an exchange.
+    ///
+    /// The side to move may always stand pat and take the static evaluation instead, unless it
+    /// is in check, in which case every move is searched.
+    fn quiescence(
+        &mut self,
+        game_state: &mut GameState,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+
+        if let Some(game_result) = game_state.get_game_result() {
+            return match game_result.get_termination() {
+                Termination::Checkmate => -MATE_SCORE + ply as i32,
+                _ => 0,
+            };
+        }
+        let position = *game_state.get_position();
+        let stand_pat = evaluate_position(&position);
+        if ply >= MAX_DEPTH || self.should_stop() {
+            return stand_pat;
+        }
+
+        let in_check = position.is_check();
+        if !in_check {
+            if stand_pat >= beta {
+                return beta;
+            }
+            alpha = alpha.max(stand_pat);
+        }
+
+        // Most promising exchanges first, and none that lose material
+        let mut moves: Vec<(i32, Move)> = game_state
+            .get_valid_moves()
+            .iter()
+            .filter(|to_move| in_check || is_tactical(to_move))
+            .map(|to_move| (position.see(to_move), *to_move))
+            .filter(|(see, _)| in_check || *see >= 0)
+            .collect();
+        moves.sort_by_key(|(see, _)| -see);
+
+        for (see, to_move) in moves {
+            // Even winning the exchange and then some would not reach alpha
+            if !in_check && stand_pat + see + DELTA_MARGIN <= alpha {
+                continue;
+            }
+
+            game_state.make_new_move(to_move);
+            let score = -self.quiescence(game_state, ply + 1, -beta, -alpha);
+            game_state.undo_last_move();
+
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+}
+
+/// Captures and promotions, the moves that change the material balance.
+fn is_tactical(to_move: &Move) -> bool {
+    to_move.piece_captured != Square::Empty
+        || matches!(
+            to_move.special_move,
+            Some(SpecialMove::EnPassant | SpecialMove::PawnPromotion(_))
+        )
+}
+
+impl GameState {
+    /// Looks for the best move with an iterative deepening alpha-beta search.
+    ///
+    /// The search plays its moves on a copy of the game, so the game itself is not touched.
+    /// It starts with an empty transposition table of [`DEFAULT_HASH_MB`].
+    pub fn search(&self, limits: &SearchLimits) -> SearchResult {
+        self.search_with_progress(limits, &mut TranspositionTable::default(), |_| {})
+    }
+
+    /// Same as [`GameState::search`], calling `progress` after every completed iteration.
+    ///
+    /// `table` keeps what the search found out, for the next search to use.
+    pub fn search_with_progress(
+        &self,
+        limits: &SearchLimits,
+        table: &mut TranspositionTable,
+        mut progress: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        table.new_search();
+        let mut searcher = Searcher {
+            limits,
+            table,
+            ordering: MoveOrdering::new(),
+            start: Instant::now(),
+            nodes: 0,
+            cutoffs: 0,
+            first_move_cutoffs: 0,
+            stopped: false,
+        };
+        let mut result = SearchResult {
+            best_move: self.get_valid_moves().first().copied(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            time: Duration::ZERO,
+            principal_variation: Vec::new(),
+            hashfull: 0,
+            cutoffs: 0,
+            first_move_cutoffs: 0,
+        };
+        if self.is_game_over() {
+            return result;
+        }
+
+        let mut game_state = self.clone();
+        let mut root_moves = self.get_valid_moves().clone();
+        searcher
+            .ordering
+            .order(&mut root_moves, self.get_position(), None, 0);
+        for depth in 1..=limits.depth.min(MAX_DEPTH) {
+            let mut alpha = -MATE_SCORE;
+            let mut principal_variation = Vec::new();
+            let mut child_variation = Vec::new();
+
+            for &to_move in &root_moves {
+                game_state.make_new_move(to_move);
+                let score = -searcher.negamax(
+                    &mut game_state,
+                    depth - 1,
+                    1,
+                    -MATE_SCORE,
+                    -alpha,
+                    &mut child_variation,
+                );
+                game_state.undo_last_move();
+
+                if searcher.stopped {
+                    break;
+                }
+                if score > alpha || principal_variation.is_empty() {
+                    alpha = score;
+                    principal_variation.clear();
+                    principal_variation.push(to_move);
+                    principal_variation.append(&mut child_variation);
+                }
+            }
+
+            if searcher.stopped {
+                break;
+            }
+
+            // Searching the best move first lets the next iteration cut off more moves
+            let best_move = principal_variation[0];
+            if let Some(index) = root_moves.iter().position(|root| *root == best_move) {
+                root_moves[..=index].rotate_right(1);
+            }
+
+            result = SearchResult {
+                best_move: Some(best_move),
+                score: alpha,
+                depth,
+                nodes: searcher.nodes,
+                time: searcher.start.elapsed(),
+                principal_variation,
+                hashfull: searcher.table.hashfull(),
+                cutoffs: searcher.cutoffs,
+                first_move_cutoffs: searcher.first_move_cutoffs,
+            };
+            progress(&result);
+        }
+
+        result.nodes = searcher.nodes;
+        result.time = searcher.start.elapsed();
+        result.hashfull = searcher.table.hashfull();
+        result.cutoffs = searcher.cutoffs;
+        result.first_move_cutoffs = searcher.first_move_cutoffs;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SearchLimits, MATE_SCORE};
+    use crate::prelude::*;
+
+    #[test]
+    fn finds_mate_in_one() {
+        let game_state = GameState::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let result = game_state.search(&SearchLimits {
+            depth: 2,
+            ..SearchLimits::default()
+        });
+        assert_eq!(result.best_move.unwrap().to_uci(), "a1a8");
+        assert_eq!(result.score, MATE_SCORE - 1);
+        assert_eq!(result.mate_in(), Some(1));
+    }
+
+    #[test]
+    fn wins_material_and_reports_the_variation() {
+        let game_state = GameState::from_fen("4k3/8/8/3q4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        let result = game_state.search(&SearchLimits {
+            depth: 3,
+            ..SearchLimits::default()
+        });
+        assert_eq!(result.best_move.unwrap().to_uci(), "d2d5");
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.principal_variation.len(), 3);
+        assert_eq!(result.principal_variation[0].to_uci(), "d2d5");
+        assert!(result.mate_in().is_none());
+    }
+
+    #[test]
+    fn looks_past_the_horizon_of_captures() {
+        // At depth 1 the pawn on d5 looks free, but the quiescence search sees c6xd5
+        let game_state = GameState::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let result = game_state.search(&SearchLimits {
+            depth: 1,
+            ..SearchLimits::default()
+        });
+        assert_ne!(result.best_move.unwrap().to_uci(), "d1d5");
+        assert!(result.score > 0);
+    }
+
+    #[test]
+    fn reuses_the_transposition_table() {
+        let game_state = GameState::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let limits = SearchLimits {
+            depth: 4,
+            ..SearchLimits::default()
+        };
+        let mut table = TranspositionTable::new(1);
+        let first = game_state.search_with_progress(&limits, &mut table, |_| {});
+        assert!(first.hashfull > 0);
+
+        // Everything the second search needs is already known
+        let second = game_state.search_with_progress(&limits, &mut table, |_| {});
+        assert!(second.nodes < first.nodes / 2);
+        assert_eq!(second.score, first.score);
+    }
+
+    #[test]
+    fn orders_moves_well() {
+        let game_state = GameState::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let result = game_state.search(&SearchLimits {
+            depth: 4,
+            ..SearchLimits::default()
+        });
+        assert!(result.first_move_cutoffs <= result.cutoffs);
+        assert!(result.first_move_cutoff_percentage().unwrap() > 90.0);
+    }
+
+    #[test]
+    fn respects_node_limit_and_leaves_state_untouched() {
+        let game_state = GameState::new();
+        let result = game_state.search(&SearchLimits {
+            depth: 10,
+            nodes: Some(500),
+            ..SearchLimits::default()
+        });
+        assert!(result.depth < 10);
+        assert!(result.best_move.is_some());
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert!(game_state.get_move_log().is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use std::cmp::Reverse;
+
+use super::{is_tactical, MAX_DEPTH};
+use crate::bitboard;
+use crate::eval::piece_value;
+use crate::prelude::*;
+
+/// Sort keys of the stages, from the first searched to the last. Within a stage, moves are
+/// ordered by the part of the key below the next stage.
+const HASH_MOVE: i32 = i32::MAX;
+const GOOD_CAPTURE: i32 = 3 << 28;
+const KILLER: i32 = 2 << 28;
+const BAD_CAPTURE: i32 = -(1 << 28);
+
+/// History scores are halved once one of them reaches this, so that they stay below the
+/// killers and newer cutoffs count more than old ones.
+const HISTORY_LIMIT: i32 = 1 << 20;
+
+/// Decides the order in which the search tries moves, so that the move refuting the
+/// position, if there is one, tends to come first and the rest can be cut off:
+///
+/// 1. the best move stored in the transposition table,
+/// 2. captures and promotions that do not lose material, most valuable victim first and
+///    least valuable attacker among those,
+/// 3. the two latest quiet moves that caused a cutoff at the same ply, the killer moves,
+/// 4. the other quiet moves, by how often they caused cutoffs anywhere in the search,
+/// 5. captures that lose material.
+pub(super) struct MoveOrdering {
+    killers: [[Option<Move>; 2]; MAX_DEPTH + 1],
+    /// Cutoff scores of quiet moves, by player, start square and end square.
+    history: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl MoveOrdering {
+    pub(super) fn new() -> Self {
+        Self {
+            killers: [[None; 2]; MAX_DEPTH + 1],
+            history: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    /// Sorts `moves` of `position`, found at `ply` plies from the root, best first.
+    pub(super) fn order(
+        &self,
+        moves: &mut [Move],
+        position: &Position,
+        hash_move: Option<Move>,
+        ply: usize,
+    ) {
+        moves.sort_by_cached_key(|to_move| Reverse(self.score(to_move, position, hash_move, ply)));
+    }
+
+    /// Remembers that `to_move`, played by `player` at `ply` with `depth` plies left, was
+    /// good enough to end the search of its position. Only quiet moves are remembered, since
+    /// captures are ordered well enough by what they win.
+    pub(super) fn record_cutoff(
+        &mut self,
+        to_move: Move,
+        player: Player,
+        depth: usize,
+        ply: usize,
+    ) {
+        if is_tactical(&to_move) {
+            return;
+        }
+
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(to_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(to_move);
+        }
+
+        // Cutoffs far from the leaves save the most work
+        let history = &mut self.history[player as usize];
+        let entry = &mut history[bitboard::square_index(to_move.start)]
+            [bitboard::square_index(to_move.end)];
+        *entry += (depth * depth) as i32;
+        if *entry >= HISTORY_LIMIT {
+            history.iter_mut().flatten().for_each(|score| *score /= 2);
+        }
+    }
+
+    fn score(
+        &self,
+        to_move: &Move,
+        position: &Position,
+        hash_move: Option<Move>,
+        ply: usize,
+    ) -> i32 {
+        if hash_move.is_some_and(|hash_move| *to_move == hash_move) {
+            return HASH_MOVE;
+        }
+
+        if is_tactical(to_move) {
+            let stage = if position.see(to_move) >= 0 {
+                GOOD_CAPTURE
+            } else {
+                BAD_CAPTURE
+            };
+            return stage + most_valuable_victim_least_valuable_attacker(to_move);
+        }
+
+        if let Some(slot) = self.killers[ply]
+            .iter()
+            .position(|killer| *killer == Some(*to_move))
+        {
+            return KILLER - slot as i32;
+        }
+
+        let Square::Occupied(player, _) = to_move.piece_moved else {
+            return 0;
+        };
+        self.history[player as usize][bitboard::square_index(to_move.start)]
+            [bitboard::square_index(to_move.end)]
+    }
+}
+
+/// Orders captures by the value of what they take, including what a promotion gains, and then
+/// by the attacker, the least valuable first.
+fn most_valuable_victim_least_valuable_attacker(to_move: &Move) -> i32 {
+    let mut victim = match to_move.piece_captured {
+        Square::Occupied(_, piece) => piece_value(piece),
+        Square::Empty if to_move.special_move == Some(SpecialMove::EnPassant) => {
+            piece_value(Piece::Pawn)
+        }
+        Square::Empty => 0,
+    };
+    if let Some(SpecialMove::PawnPromotion(Square::Occupied(_, promoted))) = to_move.special_move {
+        victim += piece_value(promoted);
+    }
+    let attacker = match to_move.piece_moved {
+        Square::Occupied(_, piece) => piece as i32,
+        Square::Empty => 0,
+    };
+    victim * 8 - attacker
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ordered(ordering: &MoveOrdering, fen: &str, hash_move: Option<&str>) -> Vec<String> {
+        let game_state = GameState::from_fen(fen).unwrap();
+        let hash_move = hash_move.map(|uci| game_state.parse_uci_move(uci).unwrap());
+        let mut moves = game_state.get_valid_moves().clone();
+        ordering.order(&mut moves, game_state.get_position(), hash_move, 1);
+        moves.iter().map(Move::to_uci).collect()
+    }
+
+    #[test]
+    fn orders_by_stage() {
+        // Qxd5 wins a pawn, Qxh5 loses the queen to the pawn on g6, and Nxa7 takes a pawn
+        // with a knight
+        let fen = "4k3/p7/2N3p1/3p3p/8/8/8/3QK3 w - - 0 1";
+        let mut ordering = MoveOrdering::new();
+
+        let moves = ordered(&ordering, fen, None);
+        assert_eq!(moves[..2], ["c6a7", "d1d5"]);
+        assert_eq!(moves.last().unwrap(), "d1h5");
+
+        let king_move = GameState::from_fen(fen)
+            .unwrap()
+            .parse_uci_move("e1f2")
+            .unwrap();
+        ordering.record_cutoff(king_move, Player::White, 3, 1);
+        let moves = ordered(&ordering, fen, Some("d1d3"));
+        assert_eq!(moves[..4], ["d1d3", "c6a7", "d1d5", "e1f2"]);
+
+        // Killers belong to their ply, history to the whole search
+        let game_state = GameState::from_fen(fen).unwrap();
+        let mut moves = game_state.get_valid_moves().clone();
+        ordering.order(&mut moves, game_state.get_position(), None, 2);
+        assert_eq!(moves[2].to_uci(), "e1f2");
+        assert!(ordering.killers[2].iter().all(Option::is_none));
+
+        // A capture causing a cutoff is neither a killer nor in the history
+        let capture = game_state.parse_uci_move("d1d5").unwrap();
+        ordering.record_cutoff(capture, Player::White, 3, 1);
+        assert_eq!(ordering.killers[1], [Some(king_move), None]);
+    }
+}
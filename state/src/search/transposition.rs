@@ -0,0 +1,211 @@
+use std::mem;
+
+use super::{MATE_SCORE, MAX_DEPTH};
+use crate::prelude::*;
+
+/// Size of a table made with [`TranspositionTable::default`].
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// How the stored score relates to the true score of the position.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(super) enum Bound {
+    Exact,
+    /// The search failed high, so the true score is at least this good.
+    Lower,
+    /// No move reached alpha, so the true score is at most this good.
+    Upper,
+}
+
+/// What an earlier search found out about a position.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct Entry {
+    /// Full Zobrist key, to tell apart positions that share a slot.
+    key: u64,
+    pub(super) best_move: Option<Move>,
+    pub(super) depth: u8,
+    score: i32,
+    pub(super) bound: Bound,
+    /// The search that stored the entry, so that older ones are replaced first.
+    age: u8,
+}
+
+impl Entry {
+    /// The stored score, with mates counted from the position at `ply` again.
+    pub(super) fn score(&self, ply: usize) -> i32 {
+        if self.score > MATE_SCORE - MAX_DEPTH as i32 {
+            self.score - ply as i32
+        } else if self.score < -MATE_SCORE + MAX_DEPTH as i32 {
+            self.score + ply as i32
+        } else {
+            self.score
+        }
+    }
+}
+
+/// Remembers searched positions by their Zobrist key, so that positions reached again through
+/// another move order or by a deeper iteration do not have to be searched from scratch.
+///
+/// The table has a fixed number of slots, a power of two so that the key picks one directly.
+/// A new entry replaces the one in its slot if that entry comes from an earlier search or was
+/// searched less deeply.
+#[derive(Clone, Debug)]
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    age: u8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    /// Makes a table using at most `megabytes` of memory, and at least one slot.
+    pub fn new(megabytes: usize) -> Self {
+        let slots = megabytes * 1024 * 1024 / mem::size_of::<Option<Entry>>();
+        let slots = match slots {
+            0 => 1,
+            slots if slots.is_power_of_two() => slots,
+            slots => slots.next_power_of_two() / 2,
+        };
+        Self {
+            entries: vec![None; slots],
+            age: 0,
+        }
+    }
+
+    /// Changes the size of the table, forgetting everything in it.
+    pub fn resize(&mut self, megabytes: usize) {
+        *self = Self::new(megabytes);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+        self.age = 0;
+    }
+
+    /// Number of slots, whatever is stored in them.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// How full the table is in permille, counting only entries of the current search, as
+    /// reported by UCI `info hashfull`. Looks at the first thousand slots only.
+    pub fn hashfull(&self) -> u16 {
+        let sample = &self.entries[..self.entries.len().min(1000)];
+        let used = sample
+            .iter()
+            .flatten()
+            .filter(|entry| entry.age == self.age)
+            .count();
+        (used * 1000 / sample.len()) as u16
+    }
+
+    /// Marks the start of a new search. Entries of earlier searches stay useful, but are
+    /// replaced first.
+    pub(super) fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    pub(super) fn probe(&self, key: u64) -> Option<Entry> {
+        self.entries[self.index(key)].filter(|entry| entry.key == key)
+    }
+
+    /// Stores what a search of `depth` plies found, with `score` seen from the position at
+    /// `ply`.
+    pub(super) fn store(
+        &mut self,
+        key: u64,
+        best_move: Option<Move>,
+        depth: usize,
+        score: i32,
+        bound: Bound,
+        ply: usize,
+    ) {
+        let index = self.index(key);
+        let depth = depth.min(u8::MAX as usize) as u8;
+        let existing = self.entries[index];
+        if let Some(existing) = existing {
+            if existing.key != key && existing.age == self.age && existing.depth > depth {
+                return;
+            }
+        }
+
+        // Mates are stored as seen from this position, since it can be reached at any ply
+        let score = if score > MATE_SCORE - MAX_DEPTH as i32 {
+            score + ply as i32
+        } else if score < -MATE_SCORE + MAX_DEPTH as i32 {
+            score - ply as i32
+        } else {
+            score
+        };
+        // A search that failed low has no best move, but an earlier one may have
+        let best_move = best_move.or_else(|| {
+            existing
+                .filter(|existing| existing.key == key)
+                .and_then(|existing| existing.best_move)
+        });
+
+        self.entries[index] = Some(Entry {
+            key,
+            best_move,
+            depth,
+            score,
+            bound,
+            age: self.age,
+        });
+    }
+
+    fn index(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_and_replacement() {
+        let table = TranspositionTable::new(1);
+        assert!(table.capacity().is_power_of_two());
+        assert!(table.capacity() * mem::size_of::<Option<Entry>>() <= 1024 * 1024);
+        assert_eq!(TranspositionTable::new(0).capacity(), 1);
+
+        let mut table = TranspositionTable::new(0);
+        table.new_search();
+        table.store(1, None, 5, 30, Bound::Exact, 0);
+        assert_eq!(table.probe(2).map(|entry| entry.depth), None);
+
+        // A shallower entry of another position does not push out a deeper one of this search
+        table.store(2, None, 3, 10, Bound::Lower, 0);
+        assert_eq!(table.probe(1).unwrap().depth, 5);
+        assert_eq!(table.hashfull(), 1000);
+
+        // Entries of earlier searches give way
+        table.new_search();
+        assert_eq!(table.hashfull(), 0);
+        table.store(2, None, 3, 10, Bound::Lower, 0);
+        let entry = table.probe(2).unwrap();
+        assert_eq!(
+            (entry.depth, entry.bound, entry.score(0)),
+            (3, Bound::Lower, 10)
+        );
+        assert!(table.probe(1).is_none());
+
+        table.clear();
+        assert!(table.probe(2).is_none());
+    }
+
+    #[test]
+    fn mate_scores_count_from_the_position() {
+        let mut table = TranspositionTable::new(1);
+        // Mate found 3 plies below the root, 5 plies below the stored position
+        table.store(7, None, 4, MATE_SCORE - 8, Bound::Exact, 3);
+        assert_eq!(table.probe(7).unwrap().score(3), MATE_SCORE - 8);
+        assert_eq!(table.probe(7).unwrap().score(1), MATE_SCORE - 6);
+        table.store(7, None, 4, -MATE_SCORE + 8, Bound::Exact, 3);
+        assert_eq!(table.probe(7).unwrap().score(1), -MATE_SCORE + 6);
+    }
+}
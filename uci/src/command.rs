@@ -0,0 +1,227 @@
+/// Limits sent along with a `go` command. Times are in milliseconds.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GoParameters {
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub infinite: bool,
+}
+
+/// A command sent by the GUI to the engine.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Uci,
+    Debug(bool),
+    IsReady,
+    SetOption {
+        name: String,
+        value: Option<String>,
+    },
+    UciNewGame,
+    /// `fen` is `None` for `position startpos`.
+    Position {
+        fen: Option<String>,
+        moves: Vec<String>,
+    },
+    Go(GoParameters),
+    Stop,
+    PonderHit,
+    Quit,
+}
+
+impl Command {
+    /// Reads one line of input. Unknown commands and malformed arguments give `None`, since
+    /// the protocol asks engines to ignore them.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut tokens = line.split_whitespace();
+
+        // Unknown tokens before the command name are skipped, as the protocol requires
+        let name = tokens.find(|token| {
+            matches!(
+                *token,
+                "uci"
+                    | "debug"
+                    | "isready"
+                    | "setoption"
+                    | "ucinewgame"
+                    | "position"
+                    | "go"
+                    | "stop"
+                    | "ponderhit"
+                    | "quit"
+            )
+        })?;
+        let arguments: Vec<&str> = tokens.collect();
+
+        let command = match name {
+            "uci" => Self::Uci,
+            "debug" => Self::Debug(arguments.first() == Some(&"on")),
+            "isready" => Self::IsReady,
+            "setoption" => Self::parse_set_option(&arguments)?,
+            "ucinewgame" => Self::UciNewGame,
+            "position" => Self::parse_position(&arguments)?,
+            "go" => Self::Go(Self::parse_go(&arguments)),
+            "stop" => Self::Stop,
+            "ponderhit" => Self::PonderHit,
+            _ => Self::Quit,
+        };
+        Some(command)
+    }
+
+    fn parse_set_option(arguments: &[&str]) -> Option<Self> {
+        let name_start = arguments.iter().position(|token| *token == "name")? + 1;
+        let value_start = arguments.iter().position(|token| *token == "value");
+
+        let name_end = value_start.unwrap_or(arguments.len());
+        if name_start >= name_end {
+            return None;
+        }
+        let name = arguments[name_start..name_end].join(" ");
+        let value = value_start.map(|start| arguments[start + 1..].join(" "));
+
+        Some(Self::SetOption { name, value })
+    }
+
+    fn parse_position(arguments: &[&str]) -> Option<Self> {
+        let moves_start = arguments.iter().position(|token| *token == "moves");
+        let setup = &arguments[..moves_start.unwrap_or(arguments.len())];
+
+        let fen = match setup.split_first() {
+            Some((&"startpos", [])) => None,
+            Some((&"fen", fields)) if !fields.is_empty() => Some(fields.join(" ")),
+            _ => return None,
+        };
+        let moves = match moves_start {
+            Some(start) => arguments[start + 1..]
+                .iter()
+                .map(|token| token.to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Some(Self::Position { fen, moves })
+    }
+
+    fn parse_go(arguments: &[&str]) -> GoParameters {
+        let mut parameters = GoParameters::default();
+
+        let mut tokens = arguments.iter();
+        while let Some(token) = tokens.next() {
+            if *token == "infinite" {
+                parameters.infinite = true;
+                continue;
+            }
+            let field = match *token {
+                "depth" => {
+                    parameters.depth = tokens.next().and_then(|value| value.parse().ok());
+                    continue;
+                }
+                "nodes" => &mut parameters.nodes,
+                "movetime" => &mut parameters.movetime,
+                "wtime" => &mut parameters.wtime,
+                "btime" => &mut parameters.btime,
+                "winc" => &mut parameters.winc,
+                "binc" => &mut parameters.binc,
+                "movestogo" => &mut parameters.movestogo,
+                _ => continue,
+            };
+            // Negative clock values are sent by some GUIs when the engine is out of time
+            *field = tokens
+                .next()
+                .and_then(|value| value.parse::<i64>().ok())
+                .map(|value| value.max(0) as u64);
+        }
+
+        parameters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, GoParameters};
+
+    #[test]
+    fn simple_commands() {
+        assert_eq!(Command::parse("uci"), Some(Command::Uci));
+        assert_eq!(Command::parse("  isready  "), Some(Command::IsReady));
+        assert_eq!(Command::parse("joho debug on"), Some(Command::Debug(true)));
+        assert_eq!(Command::parse("debug off"), Some(Command::Debug(false)));
+        assert_eq!(Command::parse("quit"), Some(Command::Quit));
+        assert_eq!(Command::parse("unknown"), None);
+        assert_eq!(Command::parse(""), None);
+    }
+
+    #[test]
+    fn position() {
+        assert_eq!(
+            Command::parse("position startpos moves e2e4 e7e5"),
+            Some(Command::Position {
+                fen: None,
+                moves: vec![String::from("e2e4"), String::from("e7e5")],
+            })
+        );
+        assert_eq!(
+            Command::parse("position fen 4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"),
+            Some(Command::Position {
+                fen: Some(String::from("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1")),
+                moves: Vec::new(),
+            })
+        );
+        assert_eq!(Command::parse("position"), None);
+        assert_eq!(Command::parse("position fen moves e2e4"), None);
+    }
+
+    #[test]
+    fn go() {
+        assert_eq!(
+            Command::parse("go wtime 300000 btime -20 winc 2000 binc 2000 movestogo 12"),
+            Some(Command::Go(GoParameters {
+                wtime: Some(300000),
+                btime: Some(0),
+                winc: Some(2000),
+                binc: Some(2000),
+                movestogo: Some(12),
+                ..GoParameters::default()
+            }))
+        );
+        assert_eq!(
+            Command::parse("go depth 6 nodes 10000 movetime 500"),
+            Some(Command::Go(GoParameters {
+                depth: Some(6),
+                nodes: Some(10000),
+                movetime: Some(500),
+                ..GoParameters::default()
+            }))
+        );
+        assert_eq!(
+            Command::parse("go infinite"),
+            Some(Command::Go(GoParameters {
+                infinite: true,
+                ..GoParameters::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn set_option() {
+        assert_eq!(
+            Command::parse("setoption name Clear Hash"),
+            Some(Command::SetOption {
+                name: String::from("Clear Hash"),
+                value: None,
+            })
+        );
+        assert_eq!(
+            Command::parse("setoption name Hash value 64"),
+            Some(Command::SetOption {
+                name: String::from("Hash"),
+                value: Some(String::from("64")),
+            })
+        );
+    }
+}
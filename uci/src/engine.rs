@@ -0,0 +1,424 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use state::prelude::*;
+
+use crate::command::{Command, GoParameters};
+
+struct RunningSearch {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+}
+
+/// Answers UCI commands, searching on a separate thread so that `stop` and `isready` are
+/// handled while the engine is thinking.
+pub struct Engine<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    game_state: GameState,
+    /// Shared with the search thread, which holds the lock while it runs.
+    table: Arc<Mutex<TranspositionTable>>,
+    debug: bool,
+    search: Option<RunningSearch>,
+}
+
+impl<W: Write + Send + 'static> Engine<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output: Arc::new(Mutex::new(output)),
+            game_state: GameState::new(),
+            table: Arc::new(Mutex::new(TranspositionTable::default())),
+            debug: false,
+            search: None,
+        }
+    }
+
+    /// Executes every command in `input` until `quit` or the end of the input.
+    ///
+    /// A search still running at the end of the input is allowed to finish, unless it is an
+    /// infinite one, so that scripted command sequences can be piped into the engine.
+    pub fn run(&mut self, input: impl BufRead) {
+        for line in input.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(command) = Command::parse(&line) {
+                if !self.execute(command) {
+                    self.stop_search();
+                    return;
+                }
+            }
+        }
+        if let Some(search) = self.search.take() {
+            if search.infinite {
+                search.stop.store(true, Ordering::Relaxed);
+            }
+            // A panicking search has nothing left to report
+            let _ = search.handle.join();
+        }
+    }
+
+    /// Executes a single command, returning `false` once the engine should quit.
+    pub fn execute(&mut self, command: Command) -> bool {
+        match command {
+            Command::Uci => {
+                send(
+                    &self.output,
+                    &format!("id name chess {}", env!("CARGO_PKG_VERSION")),
+                );
+                send(&self.output, "id author niblit");
+                send(
+                    &self.output,
+                    &format!(
+                        "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                    ),
+                );
+                send(&self.output, "uciok");
+            }
+            Command::Debug(debug) => self.debug = debug,
+            Command::IsReady => send(&self.output, "readyok"),
+            Command::SetOption { name, value } => self.set_option(&name, value.as_deref()),
+            Command::UciNewGame => {
+                self.stop_search();
+                self.game_state = GameState::new();
+                lock(&self.table).clear();
+            }
+            Command::Position { fen, moves } => {
+                self.stop_search();
+                self.set_position(fen, &moves);
+            }
+            Command::Go(parameters) => {
+                self.stop_search();
+                self.start_search(parameters);
+            }
+            Command::Stop => self.stop_search(),
+            Command::PonderHit => {}
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    fn set_option(&mut self, name: &str, value: Option<&str>) {
+        if !name.eq_ignore_ascii_case("Hash") {
+            send(&self.output, &format!("info string unknown option {name}"));
+            return;
+        }
+        match value.and_then(|value| value.parse::<usize>().ok()) {
+            Some(megabytes) if (1..=MAX_HASH_MB).contains(&megabytes) => {
+                self.stop_search();
+                lock(&self.table).resize(megabytes);
+            }
+            _ => send(
+                &self.output,
+                &format!("info string Hash must be between 1 and {MAX_HASH_MB} MB"),
+            ),
+        }
+    }
+
+    fn set_position(&mut self, fen: Option<String>, moves: &[String]) {
+        self.game_state = match fen {
+            None => GameState::new(),
+            Some(fen) => match GameState::from_fen(&fen) {
+                Ok(game_state) => game_state,
+                Err(error) => {
+                    send(&self.output, &format!("info string {error}"));
+                    GameState::new()
+                }
+            },
+        };
+
+        for uci in moves {
+            if let Some(game_result) = self.game_state.get_game_result() {
+                let error = IllegalMoveError::GameOver(game_result);
+                send(&self.output, &format!("info string {error}"));
+                break;
+            }
+            match self.game_state.parse_uci_move(uci) {
+                Ok(to_move) => self.game_state.make_new_move(to_move),
+                Err(error) => {
+                    send(&self.output, &format!("info string {error}"));
+                    break;
+                }
+            }
+        }
+
+        if self.debug {
+            send(
+                &self.output,
+                &format!("info string position {}", self.game_state.to_fen()),
+            );
+        }
+    }
+
+    fn start_search(&mut self, parameters: GoParameters) {
+        let stop = Arc::new(AtomicBool::new(false));
+        let limits = search_limits(&parameters, self.game_state.get_turn(), &stop);
+        let game_state = self.game_state.clone();
+        let table = Arc::clone(&self.table);
+        let output = Arc::clone(&self.output);
+        let infinite = parameters.infinite;
+        let debug = self.debug;
+
+        let thread_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let mut table = lock(&table);
+            let result = game_state.search_with_progress(&limits, &mut table, |progress| {
+                send(&output, &info(progress));
+            });
+            drop(table);
+
+            if let Some(percentage) = result.first_move_cutoff_percentage().filter(|_| debug) {
+                send(
+                    &output,
+                    &format!("info string {percentage:.1}% of cutoffs on the first move"),
+                );
+            }
+
+            // The protocol forbids answering an infinite search before being told to stop
+            while infinite && !thread_stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            let best_move = result
+                .best_move
+                .map_or(String::from("0000"), |best_move| best_move.to_uci());
+            send(&output, &format!("bestmove {best_move}"));
+        });
+
+        self.search = Some(RunningSearch {
+            handle,
+            stop,
+            infinite,
+        });
+    }
+
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            // A panicking search has nothing left to report
+            let _ = search.handle.join();
+        }
+    }
+}
+
+/// Largest transposition table the `Hash` option accepts, in megabytes.
+const MAX_HASH_MB: usize = 4096;
+
+/// Time kept in reserve so that answering the GUI never makes the engine lose on time.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+/// Time spent on a `go` without any limit. The engine does not offer the `Ponder` option, so
+/// `go ponder` gets no special treatment and falls back to this as well.
+const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(5);
+
+fn search_limits(parameters: &GoParameters, turn: Player, stop: &Arc<AtomicBool>) -> SearchLimits {
+    let (remaining, increment) = match turn {
+        Player::White => (parameters.wtime, parameters.winc),
+        Player::Black => (parameters.btime, parameters.binc),
+    };
+
+    let time = if parameters.infinite {
+        None
+    } else if let Some(movetime) = parameters.movetime {
+        Some(Duration::from_millis(movetime).saturating_sub(MOVE_OVERHEAD))
+    } else {
+        remaining.map(|remaining| {
+            let remaining = Duration::from_millis(remaining);
+            let increment = Duration::from_millis(increment.unwrap_or(0));
+            // Spread the clock over the moves left, or over a typical game length
+            let moves_left = parameters.movestogo.unwrap_or(30).max(1) as u32;
+            let budget = remaining / moves_left + increment * 3 / 4;
+            budget.min(remaining.saturating_sub(MOVE_OVERHEAD))
+        })
+    };
+    // Without any limit the search would never end, since no `stop` is coming for it
+    let unlimited =
+        !parameters.infinite && parameters.depth.is_none() && parameters.nodes.is_none();
+    let time = time.or(Some(DEFAULT_MOVE_TIME).filter(|_| unlimited));
+
+    SearchLimits {
+        depth: parameters.depth.unwrap_or(MAX_DEPTH),
+        nodes: parameters.nodes,
+        time,
+        stop: Some(Arc::clone(stop)),
+    }
+}
+
+fn info(result: &SearchResult) -> String {
+    let score = match result.mate_in() {
+        Some(moves) => format!("mate {moves}"),
+        None => format!("cp {}", result.score),
+    };
+    let milliseconds = result.time.as_millis();
+    let nps = result.nodes as u128 * 1000 / milliseconds.max(1);
+    let principal_variation: Vec<String> = result
+        .principal_variation
+        .iter()
+        .map(|to_move| to_move.to_uci())
+        .collect();
+    format!(
+        "info depth {} score {score} nodes {} nps {nps} hashfull {} time {milliseconds} pv {}",
+        result.depth,
+        result.nodes,
+        result.hashfull,
+        principal_variation.join(" ")
+    )
+}
+
+/// Locks the transposition table. A search that panicked leaves a table that is still usable.
+fn lock(table: &Mutex<TranspositionTable>) -> MutexGuard<'_, TranspositionTable> {
+    table.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn send<W: Write>(output: &Mutex<W>, message: &str) {
+    if let Ok(mut output) = output.lock() {
+        // There is nobody left to tell if the GUI stopped listening
+        let _ = writeln!(output, "{message}").and_then(|_| output.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use state::prelude::*;
+
+    use super::{search_limits, Engine, DEFAULT_MOVE_TIME};
+    use crate::command::GoParameters;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn run_script(script: &str) -> Vec<String> {
+        let buffer = SharedBuffer::default();
+        let mut engine = Engine::new(buffer.clone());
+        engine.run(script.as_bytes());
+
+        let output = buffer.0.lock().unwrap();
+        String::from_utf8(output.clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn handshake() {
+        let output = run_script("uci\nisready\nquit\n");
+        assert!(output[0].starts_with("id name"));
+        assert!(output.contains(&String::from(
+            "option name Hash type spin default 16 min 1 max 4096"
+        )));
+        assert!(output.contains(&String::from("uciok")));
+        assert_eq!(output.last(), Some(&String::from("readyok")));
+    }
+
+    #[test]
+    fn plays_from_a_position() {
+        let output = run_script(
+            "ucinewgame\nposition fen 7k/8/8/8/8/8/8/R3K3 w Q - 0 1 moves e1c1 h8g8\ndebug on\n\
+             position fen 7k/8/8/8/8/8/8/R3K3 w Q - 0 1 moves e1c1 h8g8\ngo depth 3\n",
+        );
+        assert_eq!(
+            output[0],
+            "info string position 6k1/8/8/8/8/8/8/2KR4 w - - 2 2"
+        );
+        assert!(output[1].starts_with("info depth 1"));
+        assert!(output[4].ends_with("% of cutoffs on the first move"));
+        assert!(output[5].starts_with("bestmove "));
+        assert_ne!(output[5], "bestmove 0000");
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let output = run_script("position startpos moves e2e4 e2e4\nposition fen 8/8 w\n");
+        assert_eq!(output[0], "info string `e2e4` is not a legal move");
+        assert!(output[1].starts_with("info string"));
+
+        let output = run_script(
+            "setoption name Hash value 1\nsetoption name Hash value 0\nsetoption name Ponder\n",
+        );
+        assert_eq!(
+            output,
+            [
+                "info string Hash must be between 1 and 4096 MB",
+                "info string unknown option Ponder"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_mate_and_reports_progress() {
+        let output = run_script("position fen 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1\ngo depth 2\n");
+        assert!(output[0].starts_with("info depth 1 score mate 1 nodes"));
+        assert!(output[0].ends_with("pv a1a8"));
+        assert_eq!(output.last(), Some(&String::from("bestmove a1a8")));
+    }
+
+    #[test]
+    fn time_management() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let parameters = GoParameters {
+            wtime: Some(60_000),
+            btime: Some(100),
+            winc: Some(1_000),
+            ..GoParameters::default()
+        };
+        let limits = search_limits(&parameters, Player::White, &stop);
+        assert_eq!(limits.time, Some(Duration::from_millis(2_750)));
+        assert_eq!(limits.depth, MAX_DEPTH);
+        let limits = search_limits(&parameters, Player::Black, &stop);
+        assert_eq!(limits.time, Some(Duration::from_millis(100) / 30));
+
+        let parameters = GoParameters {
+            movetime: Some(1_000),
+            depth: Some(5),
+            ..GoParameters::default()
+        };
+        let limits = search_limits(&parameters, Player::White, &stop);
+        assert_eq!(limits.time, Some(Duration::from_millis(950)));
+        assert_eq!(limits.depth, 5);
+
+        // A bare `go` still ends, while a depth or node limit is enough on its own
+        let limits = search_limits(&GoParameters::default(), Player::White, &stop);
+        assert_eq!(limits.time, Some(DEFAULT_MOVE_TIME));
+        let parameters = GoParameters {
+            nodes: Some(1_000),
+            ..GoParameters::default()
+        };
+        assert_eq!(search_limits(&parameters, Player::White, &stop).time, None);
+        let parameters = GoParameters {
+            infinite: true,
+            ..GoParameters::default()
+        };
+        assert_eq!(search_limits(&parameters, Player::White, &stop).time, None);
+    }
+
+    #[test]
+    fn no_legal_moves() {
+        let output = run_script("position fen 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\ngo movetime 10\n");
+        assert_eq!(output, vec![String::from("bestmove 0000")]);
+    }
+
+    #[test]
+    fn infinite_search_waits_for_stop() {
+        let output = run_script("position startpos\ngo infinite\nisready\nstop\nquit\n");
+        assert!(output.contains(&String::from("readyok")));
+        assert!(output.last().unwrap().starts_with("bestmove "));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod command;
+mod engine;
+
+pub use engine::Engine;
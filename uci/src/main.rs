@@ -0,0 +1,9 @@
+use std::io;
+
+use uci::Engine;
+
+fn main() {
+    // Commands are read from stdin and answers written to stdout, as the protocol expects
+    let mut engine = Engine::new(io::stdout());
+    engine.run(io::stdin().lock());
+}